@@ -0,0 +1,2 @@
+-- 构建历史：记录渲染失败的页面（JSON 数组：url、template、line、message）
+ALTER TABLE build_history ADD COLUMN render_errors TEXT;
@@ -0,0 +1,2 @@
+-- 构建历史：记录构建性能剖析（JSON：各阶段、插件钩子、最慢模板与页面耗时）
+ALTER TABLE build_history ADD COLUMN profile TEXT;
@@ -0,0 +1,6 @@
+-- 定时发布与到期下线：publish_at 之前状态为 scheduled，unpublish_at 之后恢复为草稿
+ALTER TABLE posts ADD COLUMN publish_at TEXT;
+ALTER TABLE posts ADD COLUMN unpublish_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_posts_publish_at   ON posts(publish_at);
+CREATE INDEX IF NOT EXISTS idx_posts_unpublish_at ON posts(unpublish_at);
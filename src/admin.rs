@@ -27,6 +27,7 @@ pub mod pages;
 pub mod plugins;
 pub mod posts;
 pub mod profile;
+pub mod scheduler;
 pub mod settings;
 pub mod tags;
 pub mod template;
@@ -44,6 +45,7 @@ pub fn router(state: AppState) -> Router {
     // 无需认证的路由
     let public_routes = Router::new()
         .route("/admin/login", get(auth::login_page).post(auth::login_submit))
+        .route("/admin/preview/{token}", get(posts::shared_preview))
         .route("/health", get(health::health_check));
 
     // 需要认证的路由
@@ -63,6 +65,8 @@ pub fn router(state: AppState) -> Router {
         .route("/admin/posts/{id}/publish", post(posts::publish_post))
         .route("/admin/posts/{id}/unpublish", post(posts::unpublish_post))
         .route("/admin/posts/{id}/autosave", post(posts::autosave_update))
+        .route("/admin/posts/{id}/preview", get(posts::preview_post))
+        .route("/admin/posts/{id}/share", post(posts::share_preview))
         // 页面管理
         .route("/admin/pages", get(pages::list_pages).post(pages::create_page))
         .route("/admin/pages/new", get(pages::new_page_page))
@@ -89,6 +93,8 @@ pub fn router(state: AppState) -> Router {
         // 构建管理
         .route("/admin/build/ws", get(build::build_status_ws))
         .route("/admin/build", get(build::build_history).post(build::trigger_build))
+        .route("/admin/build/rollback", post(build::rollback_release))
+        .route("/admin/build/{id}", get(build::build_detail))
         // 插件管理
         .route("/admin/plugins", get(plugins::list_plugins))
         .route("/admin/plugins/toggle", post(plugins::toggle_plugin))
@@ -113,11 +119,11 @@ pub fn router(state: AppState) -> Router {
         state.project_root.join(&state.config.media.upload_dir),
     );
 
-    // 静态站点服务（build 输出目录作为 fallback）
-    let static_site = tower_http::services::ServeDir::new(
-        state.project_root.join(&state.config.build.output_dir),
-    )
-    .append_index_html_on_directories(true);
+    // 静态站点服务（build 输出目录作为 fallback），未命中时返回生成的 404.html 并带 404 状态码
+    let output_dir = state.project_root.join(&state.config.build.output_dir);
+    let static_site = tower_http::services::ServeDir::new(&output_dir)
+        .append_index_html_on_directories(true)
+        .not_found_service(tower_http::services::ServeFile::new(output_dir.join("404.html")));
 
     // 后台静态资源路由（内嵌 + 主题目录覆盖）
     let admin_static_routes = Router::new()
@@ -212,7 +218,11 @@ async fn plugin_admin_page(
 
     // 构建 MiniJinja 渲染环境
     let mut env = minijinja::Environment::new();
-    crate::cbtml::filters::register_filters(&mut env, &state.config.site.url);
+    crate::cbtml::filters::register_filters(
+        &mut env,
+        &state.config.site.url,
+        crate::build::routes::UrlResolver::new(&state.config.routes),
+    );
     env.add_template("page", &compiled).ok();
 
     // 构建渲染上下文
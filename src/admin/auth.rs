@@ -41,6 +41,16 @@ struct Claims {
     jti: String,
 }
 
+/// 预览分享链接的签名内容，与会话令牌共用密钥但以 aud 区分，互相不能冒用
+#[derive(Serialize, Deserialize)]
+struct PreviewClaims {
+    sub: String,
+    aud: String,
+    exp: usize,
+}
+
+const PREVIEW_AUDIENCE: &str = "preview";
+
 // -- 密码工具 --
 
 pub fn hash_password(password: &str) -> Result<String> {
@@ -108,6 +118,40 @@ fn decode_jwt(token: &str, jwt_secret: &str) -> Result<Claims> {
     Ok(data.claims)
 }
 
+/// 为文章签发预览令牌，返回 (token, 过期时间戳)
+pub fn create_preview_token(post_id: &str, jwt_secret: &str, expires_in: &str) -> Result<(String, i64)> {
+    let duration = parse_duration(expires_in)?;
+    let exp = chrono::Utc::now().timestamp() + duration.as_secs() as i64;
+
+    let claims = PreviewClaims {
+        sub: post_id.to_owned(),
+        aud: PREVIEW_AUDIENCE.to_owned(),
+        exp: exp as usize,
+    };
+
+    let token = jsonwebtoken::encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(jwt_secret.as_bytes()),
+    )
+    .context("JWT 编码失败")?;
+
+    Ok((token, exp))
+}
+
+/// 校验预览令牌，返回文章 ID
+pub fn verify_preview_token(token: &str, jwt_secret: &str) -> Result<String> {
+    let mut validation = Validation::default();
+    validation.set_audience(&[PREVIEW_AUDIENCE]);
+    let data = jsonwebtoken::decode::<PreviewClaims>(
+        token,
+        &DecodingKey::from_secret(jwt_secret.as_bytes()),
+        &validation,
+    )
+    .context("预览令牌无效或已过期")?;
+    Ok(data.claims.sub)
+}
+
 fn build_cookie(name: &str, value: &str, max_age_secs: i64, secure: bool) -> String {
     let secure_flag = if secure { "; Secure" } else { "" };
     format!(
@@ -1,16 +1,22 @@
 use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
-use axum::extract::State;
+use axum::extract::{Form, Path, State};
 use axum::http::StatusCode;
-use axum::response::Html;
+use axum::response::{Html, IntoResponse, Redirect, Response};
 use minijinja::context;
 use sqlx::Row;
 use std::sync::Arc;
 use std::sync::atomic::Ordering;
 use std::time::Duration;
+use tokio::sync::broadcast;
 
 use crate::admin::layout::{format_datetime, html_escape};
 use crate::admin::template::{build_admin_context, render_admin};
 use crate::build::events::BuildEvent;
+use crate::build::profile::BuildProfile;
+use crate::build::stages::load::{
+    db_page_from_row, db_post_from_row, DbCategory, DbMedia, DbPage, DbPost, DbTag,
+};
+use crate::build::stages::render::RenderError;
 use crate::state::AppState;
 
 pub async fn build_history(State(state): State<AppState>) -> Html<String> {
@@ -34,7 +40,26 @@ pub async fn build_history(State(state): State<AppState>) -> Html<String> {
                 .unwrap_or_else(|| "-".to_string());
             let error_full = error.unwrap_or("");
             let error_short: String = error_full.chars().take(80).collect();
+            let render_errors: Vec<RenderError> = row
+                .get::<Option<&str>, _>("render_errors")
+                .and_then(|s| serde_json::from_str(s).ok())
+                .unwrap_or_default();
+            let render_errors: Vec<minijinja::Value> = render_errors
+                .iter()
+                .map(|e| {
+                    let location = match e.line {
+                        Some(line) => format!("{}:{}", e.template, line),
+                        None => e.template.clone(),
+                    };
+                    context! {
+                        url => html_escape(&e.url),
+                        location => html_escape(&location),
+                        message => html_escape(&e.message),
+                    }
+                })
+                .collect();
             context! {
+                id => row.get::<&str, _>("id"),
                 started_at => format_datetime(started_at),
                 trigger => html_escape(trigger),
                 status => status,
@@ -42,12 +67,25 @@ pub async fn build_history(State(state): State<AppState>) -> Html<String> {
                 finished_at => finished,
                 error => if error_short.is_empty() { None } else { Some(error_short) },
                 error_full => html_escape(error_full),
+                render_errors => render_errors,
             }
         })
         .collect();
 
+    let releases: Vec<minijinja::Value> =
+        crate::build::release::list(&state.project_root, &reload_config(&state))
+            .into_iter()
+            .map(|r| {
+                context! {
+                    id => r.id,
+                    created_at => format_datetime(&r.created_at),
+                }
+            })
+            .collect();
+
     let ctx = context! {
         builds => builds,
+        releases => releases,
         ..build_admin_context(
             "构建管理",
             "/admin/build",
@@ -63,6 +101,210 @@ pub async fn build_history(State(state): State<AppState>) -> Html<String> {
     }
 }
 
+/// 单次构建详情：页数统计、渲染错误与性能剖析
+pub async fn build_detail(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let Some(row) = state.builds.get_history(&id).await else {
+        return Redirect::to("/admin/build?toast_msg=构建记录不存在&toast_type=error").into_response();
+    };
+
+    let started_at: &str = row.get("started_at");
+    let duration_ms: Option<i64> = row.get("duration_ms");
+    let render_errors: Vec<RenderError> = row
+        .get::<Option<&str>, _>("render_errors")
+        .and_then(|s| serde_json::from_str(s).ok())
+        .unwrap_or_default();
+    let profile: Option<BuildProfile> = row
+        .get::<Option<&str>, _>("profile")
+        .and_then(|s| serde_json::from_str(s).ok());
+
+    let fmt_ms = |ms: f64| format!("{ms:.1}");
+    let profile_ctx = profile.map(|p| {
+        // 阶段耗时条形图以最慢阶段为 100%
+        let max_stage = p.stages.iter().map(|s| s.ms).fold(0.0, f64::max);
+        context! {
+            stages => p.stages.iter().map(|s| context! {
+                name => &s.name,
+                ms => fmt_ms(s.ms),
+                percent => if max_stage > 0.0 { (s.ms / max_stage * 100.0).round() } else { 0.0 },
+            }).collect::<Vec<_>>(),
+            hooks => p.hooks.iter().map(|h| context! {
+                hook => &h.hook,
+                plugin => html_escape(&h.plugin),
+                ms => fmt_ms(h.ms),
+            }).collect::<Vec<_>>(),
+            templates => p.templates.iter().map(|t| context! {
+                template => html_escape(&t.template),
+                pages => t.pages,
+                total_ms => fmt_ms(t.total_ms),
+                max_ms => fmt_ms(t.max_ms),
+            }).collect::<Vec<_>>(),
+            pages => p.pages.iter().map(|pg| context! {
+                url => html_escape(&pg.url),
+                template => html_escape(&pg.template),
+                ms => fmt_ms(pg.ms),
+            }).collect::<Vec<_>>(),
+        }
+    });
+
+    let ctx = context! {
+        build => context! {
+            started_at => format_datetime(started_at),
+            trigger => html_escape(row.get::<&str, _>("trigger")),
+            status => row.get::<&str, _>("status"),
+            duration => duration_ms.map(|d| format!("{d}ms")).unwrap_or_else(|| "-".to_string()),
+            total_pages => row.get::<Option<i64>, _>("total_pages"),
+            rebuilt => row.get::<Option<i64>, _>("rebuilt"),
+            cached => row.get::<Option<i64>, _>("cached"),
+            error => row.get::<Option<&str>, _>("error").map(html_escape),
+            failed_pages => render_errors.len(),
+        },
+        profile => profile_ctx,
+        ..build_admin_context(
+            "构建详情",
+            "/admin/build",
+            &crate::admin::settings::get_site_title(&state).await,
+            &crate::admin::settings::get_site_url(&state).await,
+            &state.plugin_admin_pages,
+        )
+    };
+
+    match render_admin(&state.admin_env, "build-detail.cbtml", ctx) {
+        Ok(html) => Html(html).into_response(),
+        Err(e) => Html(format!("模板渲染错误: {e:#}")).into_response(),
+    }
+}
+
+/// 从数据库预取构建所需的数据：插件配置、主题配置、已发布内容、分类与标签
+pub async fn load_build_params(
+    state: &AppState,
+    config: &crate::config::SiteConfig,
+    events: crate::build::events::EventSink,
+) -> crate::build::BuildParams {
+    // 预取插件配置
+    let mut plugin_configs = std::collections::HashMap::new();
+    for name in &config.plugins.enabled {
+        if let Ok(cfg) = crate::plugin::store::PluginStore::get_all(&state.db, name).await
+            && !cfg.is_empty()
+        {
+            plugin_configs.insert(name.clone(), cfg);
+        }
+    }
+
+    // 预取主题配置
+    let theme_saved_config = state.builds.load_theme_config(&config.theme.active).await;
+
+    // 预取发布状态的文章（含标签/分类关联）
+    let db_posts: Vec<DbPost> = state
+        .posts
+        .fetch_published()
+        .await
+        .iter()
+        .map(db_post_from_row)
+        .collect();
+
+    // 预取发布状态的独立页面
+    let db_pages: Vec<DbPage> = state
+        .pages
+        .fetch_published()
+        .await
+        .iter()
+        .map(db_page_from_row)
+        .collect();
+
+    let (db_categories, db_tags) = fetch_terms(state).await;
+
+    // 预取媒体尺寸（响应式图片的 width/height）
+    let db_media: Vec<DbMedia> = state
+        .media
+        .list_dimensions()
+        .await
+        .into_iter()
+        .filter_map(DbMedia::from_row)
+        .collect();
+
+    crate::build::BuildParams {
+        clean: false,
+        force: false,
+        plugin_configs,
+        theme_saved_config,
+        db_posts,
+        db_pages,
+        db_categories,
+        db_tags,
+        db_media,
+        site_settings: state.site_settings.read().await.clone(),
+        events,
+    }
+}
+
+/// 预取文章预览所需的数据：待预览文章及其前后相邻的已发布文章、主题配置、分类与标签
+///
+/// 不加载整站内容与插件配置，预览只生成这一篇文章的页面
+pub async fn load_preview_params(
+    state: &AppState,
+    config: &crate::config::SiteConfig,
+    draft: DbPost,
+) -> crate::build::BuildParams {
+    let theme_saved_config = state.builds.load_theme_config(&config.theme.active).await;
+
+    // 按构建时的顺序排列（较新、当前、较旧），发布日期相同时加载后的排序保持此顺序
+    let (newer, older) = state
+        .posts
+        .fetch_preview_neighbours(&draft.id, &draft.created_at)
+        .await;
+    let mut db_posts: Vec<DbPost> = newer.iter().map(db_post_from_row).collect();
+    db_posts.push(draft);
+    db_posts.extend(older.iter().map(db_post_from_row));
+
+    let (db_categories, db_tags) = fetch_terms(state).await;
+
+    crate::build::BuildParams {
+        clean: false,
+        force: false,
+        plugin_configs: std::collections::HashMap::new(),
+        theme_saved_config,
+        db_posts,
+        db_pages: Vec::new(),
+        db_categories,
+        db_tags,
+        db_media: Vec::new(),
+        site_settings: state.site_settings.read().await.clone(),
+        events: crate::build::events::EventSink::default(),
+    }
+}
+
+/// 预取分类表（层级、slug 与描述）与标签表
+async fn fetch_terms(state: &AppState) -> (Vec<DbCategory>, Vec<DbTag>) {
+    let db_categories = state
+        .categories
+        .list_all()
+        .await
+        .into_iter()
+        .map(DbCategory::from)
+        .collect();
+    let db_tags = state
+        .tags
+        .list_all()
+        .await
+        .into_iter()
+        .map(DbTag::from)
+        .collect();
+    (db_categories, db_tags)
+}
+
+/// 从文件重新加载配置，失败时使用启动时缓存的配置
+///
+/// 构建、历史版本列表与回滚都以磁盘上的 cblog.toml 为准，修改 output_dir、keep_releases 等后无需重启
+fn reload_config(state: &AppState) -> Arc<crate::config::SiteConfig> {
+    match crate::config::SiteConfig::load(&state.project_root) {
+        Ok(c) => Arc::new(c),
+        Err(e) => {
+            tracing::warn!("重新加载配置失败，使用缓存配置：{e}");
+            Arc::clone(&state.config)
+        }
+    }
+}
+
 /// 核心构建逻辑：防抖 + 互斥锁 + 预取数据 + 执行构建 + 记录历史
 /// 非 manual 触发会应用 2 秒防抖，manual 触发直接执行
 pub async fn spawn_build(state: &AppState, trigger: &str) {
@@ -87,67 +329,24 @@ pub async fn spawn_build(state: &AppState, trigger: &str) {
     });
 
     // 从文件重新加载配置，确保使用最新的插件启用状态
-    let config = match crate::config::SiteConfig::load(&state.project_root) {
-        Ok(c) => Arc::new(c),
-        Err(e) => {
-            tracing::warn!("重新加载配置失败，使用缓存配置：{e}");
-            Arc::clone(&state.config)
-        }
-    };
-
-    // 预取插件配置
-    let mut plugin_configs = std::collections::HashMap::new();
-    for name in &config.plugins.enabled {
-        if let Ok(cfg) = crate::plugin::store::PluginStore::get_all(&state.db, name).await
-            && !cfg.is_empty()
-        {
-            plugin_configs.insert(name.clone(), cfg);
-        }
-    }
-
-    // 预取主题配置
-    let theme_saved_config = state.builds.load_theme_config(&config.theme.active).await;
-
-    // 预取发布状态的文章
-    use crate::build::stages::load::DbPost;
-
-    let published_rows = state.posts.fetch_published().await;
-    let db_posts: Vec<DbPost> = published_rows
-        .into_iter()
-        .map(|row| {
-            let meta_str: String = row.get("meta");
-            let meta: serde_json::Value = serde_json::from_str(&meta_str).unwrap_or_default();
-            DbPost {
-                id: row.get("id"),
-                slug: row.get("slug"),
-                title: row.get("title"),
-                content: row.get("content"),
-                status: row.get("status"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-                meta,
-            }
-        })
-        .collect();
+    let config = reload_config(state);
 
     let project_root = state.project_root.clone();
     let build_events = state.build_events.clone();
-    let site_settings = state.site_settings.read().await.clone();
     let builds_repo = state.builds.clone();
+    let params = load_build_params(
+        state,
+        &config,
+        crate::build::events::EventSink::new(build_events.clone()),
+    )
+    .await;
 
     let started_at = chrono::Utc::now().to_rfc3339();
 
     let build_root = project_root.clone();
     let build_config = Arc::clone(&config);
     let result = tokio::task::spawn_blocking(move || {
-        crate::build::run(&build_root, &build_config, crate::build::BuildParams {
-            clean: false,
-            force: false,
-            plugin_configs,
-            theme_saved_config,
-            db_posts,
-            site_settings,
-        })
+        crate::build::run(&build_root, &build_config, params)
     })
     .await;
 
@@ -157,7 +356,9 @@ pub async fn spawn_build(state: &AppState, trigger: &str) {
         (chrono::Utc::now() - s.with_timezone(&chrono::Utc)).num_milliseconds()
     });
 
+    // 部分页面渲染失败时构建仍会上线，但单独标记为 partial
     let (status, error, stats) = match &result {
+        Ok(Ok(stats)) if !stats.render_errors.is_empty() => ("partial", None, Some(stats.clone())),
         Ok(Ok(stats)) => ("success", None, Some(stats.clone())),
         Ok(Err(e)) => ("failed", Some(format!("{e:#}")), None),
         Err(e) => ("failed", Some(format!("任务执行异常: {e}")), None),
@@ -170,6 +371,8 @@ pub async fn spawn_build(state: &AppState, trigger: &str) {
                 total_pages: s.total_pages,
                 rebuilt: s.rebuilt,
                 cached: s.cached,
+                pruned: s.pruned,
+                failed_pages: s.render_errors.len(),
             });
         }
         None => {
@@ -183,6 +386,13 @@ pub async fn spawn_build(state: &AppState, trigger: &str) {
     let total_pages = stats.as_ref().map(|s| s.total_pages as i64);
     let rebuilt = stats.as_ref().map(|s| s.rebuilt as i64);
     let cached = stats.as_ref().map(|s| s.cached as i64);
+    let render_errors = stats
+        .as_ref()
+        .filter(|s| !s.render_errors.is_empty())
+        .and_then(|s| serde_json::to_string(&s.render_errors).ok());
+    let profile = stats
+        .as_ref()
+        .and_then(|s| serde_json::to_string(&s.profile).ok());
 
     let _ = builds_repo.insert_history(&crate::repository::build::BuildHistoryParams {
         id: &id,
@@ -195,6 +405,8 @@ pub async fn spawn_build(state: &AppState, trigger: &str) {
         total_pages,
         rebuilt,
         cached,
+        render_errors: render_errors.as_deref(),
+        profile: profile.as_deref(),
     }).await;
 }
 
@@ -207,6 +419,38 @@ pub async fn trigger_build(State(state): State<AppState>) -> StatusCode {
     StatusCode::ACCEPTED
 }
 
+#[derive(serde::Deserialize)]
+pub struct RollbackForm {
+    pub release: String,
+}
+
+/// 回滚到指定的历史输出版本，与构建共用互斥锁，避免与进行中的构建同时切换输出目录
+pub async fn rollback_release(
+    State(state): State<AppState>,
+    Form(form): Form<RollbackForm>,
+) -> Redirect {
+    let _lock = state.build_mutex.lock().await;
+
+    let project_root = state.project_root.clone();
+    let config = reload_config(&state);
+    let result = tokio::task::spawn_blocking(move || {
+        crate::build::release::rollback(&project_root, &config, &form.release)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(())) => Redirect::to("/admin/build?toast_msg=已回滚到所选版本&toast_type=success"),
+        Ok(Err(e)) => {
+            tracing::warn!("回滚失败：{e:#}");
+            Redirect::to("/admin/build?toast_msg=回滚失败&toast_type=error")
+        }
+        Err(e) => {
+            tracing::warn!("回滚任务执行异常：{e}");
+            Redirect::to("/admin/build?toast_msg=回滚失败&toast_type=error")
+        }
+    }
+}
+
 pub async fn build_status_ws(
     ws: WebSocketUpgrade,
     State(state): State<AppState>,
@@ -216,7 +460,13 @@ pub async fn build_status_ws(
 
 async fn handle_ws(mut socket: WebSocket, state: AppState) {
     let mut rx = state.build_events.subscribe();
-    while let Ok(event) = rx.recv().await {
+    loop {
+        let event = match rx.recv().await {
+            Ok(event) => event,
+            // 客户端消费过慢时跳过积压的进度事件，后续事件仍会继续推送
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
         let json = serde_json::to_string(&event).unwrap_or_default();
         if socket.send(Message::Text(json.into())).await.is_err() {
             break;
@@ -25,6 +25,7 @@ pub async fn dashboard(State(state): State<AppState>) -> Html<String> {
             let (badge_class, status_label) = match status {
                 "published" => ("badge-success", "已发布"),
                 "draft" => ("badge-warning", "草稿"),
+                "scheduled" => ("badge-info", "定时发布"),
                 other => ("badge-neutral", other),
             };
             context! {
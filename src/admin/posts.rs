@@ -21,6 +21,12 @@ pub struct PostForm {
     pub category: Option<String>,
     pub cover_image: Option<String>,
     pub excerpt: Option<String>,
+    /// 禁止搜索引擎收录（复选框，勾选时为 "true"）
+    pub noindex: Option<String>,
+    /// 定时发布时间（RFC 3339，由编辑页脚本从本地时间转换）
+    pub publish_at: Option<String>,
+    /// 到期下线时间（RFC 3339）
+    pub unpublish_at: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -42,6 +48,18 @@ fn generate_slug(title: &str) -> String {
         .join("-")
 }
 
+/// 解析表单提交的时间，统一转换为 UTC RFC 3339；为空或格式无效时返回 None
+fn parse_schedule_time(value: Option<&str>) -> Option<String> {
+    let value = value.map(str::trim).filter(|v| !v.is_empty())?;
+    match chrono::DateTime::parse_from_rfc3339(value) {
+        Ok(dt) => Some(dt.with_timezone(&chrono::Utc).to_rfc3339()),
+        Err(e) => {
+            tracing::warn!("忽略无效的时间 {value}：{e}");
+            None
+        }
+    }
+}
+
 pub async fn list_posts(
     State(state): State<AppState>,
     Query(params): Query<ListQuery>,
@@ -68,6 +86,7 @@ pub async fn list_posts(
             let (badge_class, status_label) = match status {
                 "published" => ("badge-success", "已发布"),
                 "draft" => ("badge-warning", "草稿"),
+                "scheduled" => ("badge-info", "定时发布"),
                 other => ("badge-neutral", other),
             };
 
@@ -116,6 +135,7 @@ pub async fn new_post_page(State(state): State<AppState>) -> Response {
         id: &id, slug: &slug, title: "", content: "",
         status: "draft", meta: "{}",
         tags_str: "", category_str: "",
+        publish_at: None, unpublish_at: None,
     }).await {
         tracing::error!("创建草稿失败：{e}");
         return Redirect::to("/admin/posts").into_response();
@@ -143,12 +163,26 @@ pub async fn edit_post_page(
     let post_content: &str = post.get("content");
     let post_status: &str = post.get("status");
     let post_meta: &str = post.get("meta");
+    let publish_at: Option<&str> = post.get("publish_at");
+    let unpublish_at: Option<&str> = post.get("unpublish_at");
 
     let meta: serde_json::Value = serde_json::from_str(post_meta).unwrap_or_default();
-    let tags = meta["tags"].as_str().unwrap_or("");
-    let category = meta["category"].as_str().unwrap_or("");
+
+    // 标签/分类以关联表为准（后台改名后显示新名称），无关联时回退到旧版 meta
+    let (relation_tags, relation_category) = state.posts.taxonomy_names(post_id).await;
+    let tags = if relation_tags.is_empty() {
+        meta["tags"].as_str().unwrap_or("")
+    } else {
+        relation_tags.as_str()
+    };
+    let category = if relation_category.is_empty() {
+        meta["category"].as_str().unwrap_or("")
+    } else {
+        relation_category.as_str()
+    };
     let cover_image = meta["cover_image"].as_str().unwrap_or("");
     let excerpt = meta["excerpt"].as_str().unwrap_or("");
+    let noindex = meta["noindex"].as_bool().unwrap_or(false);
 
     // 草稿状态下隐藏自动生成的 draft-xxx slug
     let display_slug = if post_status == "draft" && post_slug.starts_with("draft-") {
@@ -176,6 +210,9 @@ pub async fn edit_post_page(
         post_category => category,
         post_cover_image => cover_image,
         post_excerpt => excerpt,
+        post_noindex => noindex,
+        post_publish_at => publish_at.unwrap_or(""),
+        post_unpublish_at => unpublish_at.unwrap_or(""),
         editor_initial_content => post_content,
     };
 
@@ -203,13 +240,25 @@ pub async fn update_post(
             }
         }
     };
-    let status = form.status.as_deref().unwrap_or("draft");
+    let publish_at = parse_schedule_time(form.publish_at.as_deref());
+    let unpublish_at = parse_schedule_time(form.unpublish_at.as_deref());
+
+    // 选择发布但 publish_at 在未来时进入定时发布状态，由后台任务到时切换
+    let now = chrono::Utc::now().to_rfc3339();
+    let status = match form.status.as_deref().unwrap_or("draft") {
+        "published" | "scheduled" if publish_at.as_deref().is_some_and(|t| t > now.as_str()) => {
+            "scheduled"
+        }
+        "scheduled" => "published",
+        other => other,
+    };
 
     let meta = serde_json::json!({
         "tags": form.tags.as_deref().unwrap_or(""),
         "category": form.category.as_deref().unwrap_or(""),
         "cover_image": form.cover_image.as_deref().unwrap_or(""),
         "excerpt": form.excerpt.as_deref().unwrap_or(""),
+        "noindex": form.noindex.is_some(),
     })
     .to_string();
 
@@ -218,6 +267,8 @@ pub async fn update_post(
         status, meta: &meta,
         tags_str: form.tags.as_deref().unwrap_or(""),
         category_str: form.category.as_deref().unwrap_or(""),
+        publish_at: publish_at.as_deref(),
+        unpublish_at: unpublish_at.as_deref(),
     }).await {
         tracing::error!("更新文章失败：{e}");
         return Redirect::to(&format!("/admin/posts/{id}"));
@@ -301,6 +352,7 @@ pub struct AutosaveBody {
     pub category: Option<String>,
     pub cover_image: Option<String>,
     pub excerpt: Option<String>,
+    pub noindex: Option<bool>,
 }
 
 pub async fn autosave_update(
@@ -327,6 +379,7 @@ pub async fn autosave_update(
         "category": body.category.as_deref().unwrap_or(""),
         "cover_image": body.cover_image.as_deref().unwrap_or(""),
         "excerpt": body.excerpt.as_deref().unwrap_or(""),
+        "noindex": body.noindex.unwrap_or(false),
     })
     .to_string();
 
@@ -343,3 +396,97 @@ pub async fn autosave_update(
             .into_response(),
     }
 }
+
+/// 用当前主题在内存中渲染文章（含未发布草稿），不写入输出目录
+async fn render_preview(state: &AppState, id: &str) -> Response {
+    use crate::build::stages::load::db_post_from_row;
+
+    let Some(row) = state.posts.fetch_for_preview(id).await else {
+        return (StatusCode::NOT_FOUND, "文章不存在").into_response();
+    };
+    let draft = db_post_from_row(&row);
+
+    let config = std::sync::Arc::clone(&state.config);
+    let params = crate::admin::build::load_preview_params(state, &config, draft).await;
+
+    let project_root = state.project_root.clone();
+    let post_id = id.to_string();
+    let result = tokio::task::spawn_blocking(move || {
+        crate::build::pipeline::render_post_preview(&project_root, &config, &params, &post_id)
+    })
+    .await;
+
+    let html = match result {
+        Ok(Ok(html)) => html,
+        Ok(Err(e)) => {
+            return (StatusCode::INTERNAL_SERVER_ERROR, format!("预览渲染失败：{e:#}")).into_response();
+        }
+        Err(e) => {
+            return (StatusCode::INTERNAL_SERVER_ERROR, format!("任务执行异常：{e}")).into_response();
+        }
+    };
+
+    // 预览页不应被搜索引擎收录，也不应被缓存
+    (
+        [
+            ("X-Robots-Tag", "noindex, nofollow"),
+            ("Cache-Control", "no-store"),
+        ],
+        Html(html),
+    )
+        .into_response()
+}
+
+pub async fn preview_post(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Response {
+    render_preview(&state, &id).await
+}
+
+/// 签发限时预览链接，供没有后台账号的审阅者查看草稿
+pub async fn share_preview(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Response {
+    if state.posts.get_by_id(&id).await.is_none() {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "文章不存在" })),
+        )
+            .into_response();
+    }
+
+    match crate::admin::auth::create_preview_token(
+        &id,
+        &state.jwt_secret,
+        &state.config.auth.preview_expires_in,
+    ) {
+        Ok((token, exp)) => {
+            let expires_at = chrono::DateTime::from_timestamp(exp, 0)
+                .map(|t| t.to_rfc3339())
+                .unwrap_or_default();
+            Json(serde_json::json!({
+                "url": format!("/admin/preview/{token}"),
+                "expires_at": expires_at,
+            }))
+            .into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": format!("{e:#}") })),
+        )
+            .into_response(),
+    }
+}
+
+/// 通过签名链接访问预览，无需登录
+pub async fn shared_preview(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Response {
+    match crate::admin::auth::verify_preview_token(&token, &state.jwt_secret) {
+        Ok(id) => render_preview(&state, &id).await,
+        Err(_) => (StatusCode::FORBIDDEN, "预览链接无效或已过期").into_response(),
+    }
+}
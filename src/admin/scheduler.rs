@@ -0,0 +1,51 @@
+use crate::state::AppState;
+
+/// 启动后台定时任务：每分钟检查定时发布与到期下线的文章，状态变化时触发构建
+pub fn spawn_post_scheduler(state: AppState) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            let now = chrono::Utc::now().to_rfc3339();
+
+            let published = match state.posts.publish_due(&now).await {
+                Ok(ids) => ids,
+                Err(e) => {
+                    tracing::warn!("切换定时发布文章失败: {e}");
+                    Vec::new()
+                }
+            };
+            let expired = match state.posts.expire_due(&now).await {
+                Ok(ids) => ids,
+                Err(e) => {
+                    tracing::warn!("下线到期文章失败: {e}");
+                    Vec::new()
+                }
+            };
+
+            for id in &published {
+                state
+                    .call_hook("after_post_publish", &serde_json::json!({ "id": id }))
+                    .await;
+            }
+            for id in &expired {
+                state
+                    .call_hook("after_post_unpublish", &serde_json::json!({ "id": id }))
+                    .await;
+            }
+
+            if published.is_empty() && expired.is_empty() {
+                continue;
+            }
+            tracing::info!(
+                "定时任务：{} 篇文章已发布，{} 篇文章已到期下线",
+                published.len(),
+                expired.len()
+            );
+            let state = state.clone();
+            tokio::spawn(async move {
+                crate::admin::build::spawn_build(&state, "scheduled").await;
+            });
+        }
+    });
+}
@@ -1,5 +1,7 @@
 use crate::admin::layout::PluginSidebarEntry;
+use crate::build::routes::UrlResolver;
 use crate::cbtml;
+use crate::config::RouteConfig;
 use anyhow::{Context, Result};
 use minijinja::{Environment, Value, context};
 use std::collections::HashMap;
@@ -8,6 +10,7 @@ use std::path::Path;
 /// 内嵌的默认后台模板（编译进二进制，保证即使主题目录缺失也能正常渲染）
 const DEFAULT_TEMPLATES: &[(&str, &str)] = &[
     ("base.cbtml", include_str!("../../themes/aurora/templates/admin/base.cbtml")),
+    ("build-detail.cbtml", include_str!("../../themes/aurora/templates/admin/build-detail.cbtml")),
     ("build.cbtml", include_str!("../../themes/aurora/templates/admin/build.cbtml")),
     ("categories.cbtml", include_str!("../../themes/aurora/templates/admin/categories.cbtml")),
     ("dashboard.cbtml", include_str!("../../themes/aurora/templates/admin/dashboard.cbtml")),
@@ -36,11 +39,16 @@ const DEFAULT_TEMPLATES: &[(&str, &str)] = &[
 /// 构建后台专用 MiniJinja 渲染环境
 ///
 /// 先加载内嵌的默认模板（保证后台始终可用），再检查用户主题目录是否有覆盖模板
-pub fn build_admin_env(project_root: &Path, theme_name: &str, site_url: &str) -> Result<Environment<'static>> {
+pub fn build_admin_env(
+    project_root: &Path,
+    theme_name: &str,
+    site_url: &str,
+    routes: &RouteConfig,
+) -> Result<Environment<'static>> {
     let mut env = Environment::new();
 
     // 复用前台已有的过滤器
-    cbtml::filters::register_filters(&mut env, site_url);
+    cbtml::filters::register_filters(&mut env, site_url, UrlResolver::new(routes));
 
     // 后台专用过滤器
     env.add_filter("format_datetime", filter_format_datetime);
@@ -1,11 +1,16 @@
 pub mod events;
 pub mod graph;
 pub mod incremental;
+pub mod manifest;
+pub mod minify;
 pub mod pipeline;
+pub mod profile;
+pub mod release;
+pub mod routes;
 pub mod stages;
 
 use crate::admin::settings::SiteSettings;
-use crate::build::stages::load::DbPost;
+use crate::build::stages::load::{DbCategory, DbMedia, DbPage, DbPost, DbTag};
 use crate::config::SiteConfig;
 use anyhow::Result;
 use incremental::BuildStats;
@@ -19,7 +24,42 @@ pub struct BuildParams {
     pub plugin_configs: HashMap<String, HashMap<String, serde_json::Value>>,
     pub theme_saved_config: HashMap<String, serde_json::Value>,
     pub db_posts: Vec<DbPost>,
+    pub db_pages: Vec<DbPage>,
+    pub db_categories: Vec<DbCategory>,
+    pub db_tags: Vec<DbTag>,
+    pub db_media: Vec<DbMedia>,
     pub site_settings: SiteSettings,
+    /// 构建进度事件出口
+    pub events: events::EventSink,
+}
+
+/// 从项目数据库同步读取构建所需的数据（命令行构建与开发服务器使用）
+pub fn load_params_sync(project_root: &Path, config: &SiteConfig) -> BuildParams {
+    let db_path = project_root.join("cblog.db");
+    let mut site_settings = SiteSettings::load_sync(&db_path);
+    if let Some(url) = &config.build.base_url {
+        site_settings.site_url = url.clone();
+    }
+    BuildParams {
+        clean: false,
+        force: false,
+        plugin_configs: crate::plugin::store::load_all_configs_sync(&db_path, &config.plugins.enabled),
+        theme_saved_config: crate::theme::config::load_theme_config_sync(
+            &db_path,
+            &config.theme.active,
+        ),
+        db_posts: stages::load::fetch_db_posts_sync(
+            &db_path,
+            config.build.drafts,
+            config.build.future,
+        ),
+        db_pages: stages::load::fetch_db_pages_sync(&db_path, config.build.drafts),
+        db_categories: stages::load::fetch_db_categories_sync(&db_path),
+        db_tags: stages::load::fetch_db_tags_sync(&db_path),
+        db_media: stages::load::fetch_db_media_sync(&db_path),
+        site_settings,
+        events: events::EventSink::default(),
+    }
 }
 
 pub fn run(
@@ -28,32 +68,60 @@ pub fn run(
     params: BuildParams,
 ) -> Result<BuildStats> {
     let output_dir = project_root.join(&config.build.output_dir);
+    let cache_dir = project_root.join(&config.build.cache_dir);
+    // 当前输出的清单随其一起归档，需在 clean 清除缓存前读取
+    let previous_manifest = std::fs::read(cache_dir.join(manifest::FILE_NAME)).ok();
 
-    if params.clean {
-        if output_dir.exists() {
-            std::fs::remove_dir_all(&output_dir)?;
-            tracing::info!("已清除输出目录：{}", output_dir.display());
-        }
-        let cache_dir = project_root.join(&config.build.cache_dir);
-        if cache_dir.exists() {
-            std::fs::remove_dir_all(&cache_dir)?;
-            tracing::info!("已清除缓存目录：{}", cache_dir.display());
-        }
+    // clean 构建不动当前输出，从空暂存目录全量重建后照常切换，失败时线上输出保持不变
+    if params.clean && cache_dir.exists() {
+        std::fs::remove_dir_all(&cache_dir)?;
+        tracing::info!("已清除缓存目录：{}", cache_dir.display());
     }
 
-    std::fs::create_dir_all(&output_dir)?;
+    // 输出目录不存在（首次构建或被手动删除）时只能全量重建
+    let output_missing = !output_dir.exists();
+    if output_missing {
+        tracing::info!("输出目录不存在，将执行全量重建");
+    }
+
+    // 构建写入暂存目录，成功后整体切换，失败时保持当前输出不变
+    let prepare_started = std::time::Instant::now();
+    let staging = release::prepare_staging(project_root, config, params.clean)?;
+    let prepare_elapsed = prepare_started.elapsed();
 
     // clean 模式下缓存已被清除，等同于 force
-    let force = params.force || params.clean;
-    let stats = pipeline::execute(
-        project_root,
-        config,
-        params.plugin_configs,
-        params.theme_saved_config,
-        params.db_posts,
-        params.site_settings,
-        force,
-    )?;
+    let force = params.force || params.clean || output_missing;
+    let events = params.events.clone();
+    let (mut stats, caches) = match pipeline::execute(project_root, config, &staging, params, force) {
+        Ok(result) => result,
+        Err(e) => {
+            if let Err(clean_err) = std::fs::remove_dir_all(&staging) {
+                tracing::warn!("清除暂存目录失败：{clean_err}");
+            }
+            return Err(e);
+        }
+    };
+    stats.profile.stages.insert(
+        0,
+        profile::StageTiming::new("prepare", prepare_elapsed),
+    );
+
+    // 没有页面重建也没有文件被清理时输出与当前一致，不切换以免挤掉有用的历史版本
+    if stats.rebuilt == 0 && stats.pruned == 0 && !stats.assets_changed && !output_missing {
+        std::fs::remove_dir_all(&staging)?;
+        caches.save(&events);
+        return Ok(stats);
+    }
+
+    // 缓存只在切换成功后保存；切换失败时删除旧缓存，下次构建全量重建并重新切换
+    let publish_started = std::time::Instant::now();
+    if let Err(e) = release::publish(project_root, config, &staging, previous_manifest) {
+        pipeline::BuildCaches::discard(&cache_dir);
+        return Err(e);
+    }
+    caches.save(&events);
+    stats.profile.push_stage("publish", publish_started.elapsed());
+    tracing::info!("已切换输出目录：{}", output_dir.display());
 
     Ok(stats)
 }
@@ -1,4 +1,5 @@
 use serde::Serialize;
+use tokio::sync::broadcast;
 
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -21,13 +22,63 @@ pub enum BuildEvent {
     StageEnd {
         stage: String,
     },
+    /// 渲染进度：已处理 done / 共 total 个页面
+    RenderProgress {
+        done: usize,
+        total: usize,
+    },
+    Warning {
+        message: String,
+    },
     Finished {
         total_ms: u64,
         total_pages: usize,
         rebuilt: usize,
         cached: usize,
+        pruned: usize,
+        failed_pages: usize,
     },
     Failed {
         error: String,
     },
 }
+
+/// 构建事件出口：后台触发的构建转发到 WebSocket，命令行构建时为空
+#[derive(Debug, Clone, Default)]
+pub struct EventSink(Option<broadcast::Sender<BuildEvent>>);
+
+impl EventSink {
+    pub fn new(sender: broadcast::Sender<BuildEvent>) -> Self {
+        Self(Some(sender))
+    }
+
+    /// 发送事件；没有订阅者时丢弃
+    pub fn emit(&self, event: BuildEvent) {
+        if let Some(sender) = &self.0 {
+            let _ = sender.send(event);
+        }
+    }
+
+    pub fn stage_begin(&self, stage: &str) {
+        self.emit(BuildEvent::StageBegin {
+            stage: stage.to_owned(),
+        });
+    }
+
+    pub fn stage_end(&self, stage: &str) {
+        self.emit(BuildEvent::StageEnd {
+            stage: stage.to_owned(),
+        });
+    }
+
+    pub fn progress(&self, done: usize, total: usize) {
+        self.emit(BuildEvent::RenderProgress { done, total });
+    }
+
+    /// 记录警告日志并推送到前端
+    pub fn warn(&self, message: impl Into<String>) {
+        let message = message.into();
+        tracing::warn!("{message}");
+        self.emit(BuildEvent::Warning { message });
+    }
+}
@@ -1,22 +1,32 @@
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
-/// 模板依赖图：记录每个模板被哪些页面使用
+/// 依赖图持久化内容
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct GraphData {
+    /// 模板名 → 使用该模板的页面 URL
+    templates: HashMap<String, Vec<String>>,
+    /// 页面 URL → 页面依赖键（有序，见 `RenderPage::deps`）
+    pages: HashMap<String, Vec<String>>,
+}
+
+/// 依赖图：记录每个模板被哪些页面使用，以及每个页面引用了哪些文章
 pub struct DepGraph {
-    deps: HashMap<String, Vec<String>>,
+    data: GraphData,
     cache_path: PathBuf,
 }
 
 impl DepGraph {
-    /// 从缓存文件加载，不存在则返回空图
+    /// 从缓存文件加载，不存在或格式不兼容时返回空图
     pub fn load(cache_dir: &Path) -> Self {
         let cache_path = cache_dir.join("deps.json");
-        let deps = std::fs::read_to_string(&cache_path)
+        let data = std::fs::read_to_string(&cache_path)
             .ok()
             .and_then(|s| serde_json::from_str(&s).ok())
             .unwrap_or_default();
-        Self { deps, cache_path }
+        Self { data, cache_path }
     }
 
     /// 持久化依赖图到缓存文件
@@ -24,23 +34,39 @@ impl DepGraph {
         if let Some(parent) = self.cache_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
-        let json = serde_json::to_string_pretty(&self.deps)?;
+        let json = serde_json::to_string_pretty(&self.data)?;
         std::fs::write(&self.cache_path, json)?;
         Ok(())
     }
 
     /// 记录页面使用了某个模板
     pub fn add_dependency(&mut self, template: &str, page_url: &str) {
-        self.deps
+        self.data
+            .templates
             .entry(template.to_owned())
             .or_default()
             .push(page_url.to_owned());
     }
 
+    /// 记录页面的依赖键
+    pub fn set_page_deps(&mut self, page_url: &str, deps: &[String]) {
+        self.data.pages.insert(page_url.to_owned(), deps.to_vec());
+    }
+
+    /// 移除页面的依赖记录，下次增量构建时该页面视为新页面重新渲染
+    pub fn remove_page(&mut self, page_url: &str) {
+        self.data.pages.remove(page_url);
+    }
+
+    /// 上次构建时页面的依赖键，页面未记录时返回 None
+    pub fn page_deps(&self, page_url: &str) -> Option<&[String]> {
+        self.data.pages.get(page_url).map(Vec::as_slice)
+    }
+
     /// 获取使用了某个模板的所有页面 URL
-    #[allow(dead_code)]
     pub fn get_affected_pages(&self, template: &str) -> Vec<&str> {
-        self.deps
+        self.data
+            .templates
             .get(template)
             .map(|urls| urls.iter().map(String::as_str).collect())
             .unwrap_or_default()
@@ -48,6 +74,7 @@ impl DepGraph {
 
     /// 清空依赖图（每次构建前重建）
     pub fn clear(&mut self) {
-        self.deps.clear();
+        self.data.templates.clear();
+        self.data.pages.clear();
     }
 }
@@ -1,3 +1,5 @@
+use crate::build::profile::BuildProfile;
+use crate::build::stages::render::RenderError;
 use anyhow::Result;
 use sha2::{Digest, Sha256};
 use std::collections::{HashMap, HashSet};
@@ -9,6 +11,14 @@ pub struct BuildStats {
     pub total_pages: usize,
     pub rebuilt: usize,
     pub cached: usize,
+    /// 清理的过期输出文件数
+    pub pruned: usize,
+    /// 本次构建重新生成了主题资源（样式、脚本有变更）
+    pub assets_changed: bool,
+    /// 渲染失败的页面
+    pub render_errors: Vec<RenderError>,
+    /// 各阶段、插件钩子与页面渲染耗时
+    pub profile: BuildProfile,
 }
 
 /// 内容哈希缓存：追踪文件 SHA-256 哈希，支持增量构建
@@ -66,6 +76,16 @@ impl HashCache {
         self.hashes.insert(relative_path, hash);
     }
 
+    /// 读取某条哈希记录
+    pub fn get(&self, relative_path: &str) -> Option<&str> {
+        self.hashes.get(relative_path).map(String::as_str)
+    }
+
+    /// 删除某条哈希记录
+    pub fn remove(&mut self, relative_path: &str) {
+        self.hashes.remove(relative_path);
+    }
+
     /// 检查项目配置文件 cblog.toml 是否发生变更
     pub fn config_changed(&self, project_root: &Path) -> Result<bool> {
         let config_path = project_root.join("cblog.toml");
@@ -124,13 +144,12 @@ impl HashCache {
         Self::update_templates_recursive(&template_dir, &template_dir, self);
     }
 
-    /// 获取缓存中所有以 "post:" 开头的键
-    pub fn cached_post_keys(&self) -> Vec<String> {
-        self.hashes
-            .keys()
-            .filter(|k| k.starts_with("post:"))
-            .cloned()
-            .collect()
+    /// 按前缀筛选缓存条目，`keep` 返回 false 的条目被移除（参数为去掉前缀后的键）
+    pub fn retain_prefixed(&mut self, prefix: &str, mut keep: impl FnMut(&str) -> bool) {
+        self.hashes.retain(|k, _| match k.strip_prefix(prefix) {
+            Some(rest) => keep(rest),
+            None => true,
+        });
     }
 
     fn update_templates_recursive(base_dir: &Path, current_dir: &Path, cache: &mut HashCache) {
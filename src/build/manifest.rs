@@ -0,0 +1,102 @@
+use anyhow::Result;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// 输出清单在缓存目录中的文件名
+pub const FILE_NAME: &str = "manifest.json";
+
+/// 输出清单：记录上次构建写入输出目录的全部文件（相对输出目录的路径）
+///
+/// 本次构建结束后与上次清单对比，上次产出而本次不再产出的文件即为过期文件。
+/// 清单之外的文件（用户手动放入、插件生成）不会被清理。
+pub struct OutputManifest {
+    files: BTreeSet<String>,
+    cache_path: PathBuf,
+}
+
+impl OutputManifest {
+    /// 从缓存文件加载，不存在则返回空清单
+    pub fn load(cache_dir: &Path) -> Self {
+        let cache_path = cache_dir.join(FILE_NAME);
+        let files = std::fs::read_to_string(&cache_path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        Self { files, cache_path }
+    }
+
+    /// 持久化清单到缓存文件
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.cache_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(&self.files)?;
+        std::fs::write(&self.cache_path, json)?;
+        Ok(())
+    }
+
+    /// 用本次构建的产出替换清单，返回上次产出而本次未产出的文件
+    pub fn replace(&mut self, output_dir: &Path, produced: &[PathBuf]) -> Vec<String> {
+        let current: BTreeSet<String> = produced
+            .iter()
+            .filter_map(|p| p.strip_prefix(output_dir).ok())
+            .map(|rel| rel.to_string_lossy().replace('\\', "/"))
+            .collect();
+        let stale = self.files.difference(&current).cloned().collect();
+        self.files = current;
+        stale
+    }
+}
+
+/// 删除过期的输出文件，跳过 `protected` 中的路径（文件本身或其所在目录），
+/// 并清理删除后留下的空目录，返回实际删除的文件数
+pub fn prune(output_dir: &Path, stale: &[String], protected: &[PathBuf]) -> usize {
+    let mut pruned = 0;
+    for rel in stale {
+        let path = output_dir.join(rel);
+        if protected.iter().any(|p| path.starts_with(p)) {
+            tracing::debug!("跳过插件声明的生成文件：{}", path.display());
+            continue;
+        }
+        // media 为符号链接，只删除链接本身
+        if !path.exists() && !path.is_symlink() {
+            continue;
+        }
+        // 媒体发布方式切换后，旧清单中的路径可能已变为目录，或经由 media 符号链接指向项目源文件
+        if (path.is_dir() && !path.is_symlink()) || via_symlink(output_dir, &path) {
+            continue;
+        }
+        match std::fs::remove_file(&path) {
+            Ok(()) => {
+                pruned += 1;
+                tracing::debug!("已清理过期文件：{}", path.display());
+                remove_empty_parents(output_dir, &path);
+            }
+            Err(e) => tracing::warn!("清理过期文件失败 {}: {}", path.display(), e),
+        }
+    }
+    pruned
+}
+
+/// 路径在输出目录内的某一级父目录是否为符号链接
+fn via_symlink(output_dir: &Path, path: &Path) -> bool {
+    path.ancestors()
+        .skip(1)
+        .take_while(|d| *d != output_dir && d.starts_with(output_dir))
+        .any(Path::is_symlink)
+}
+
+/// 自下而上删除空目录，直到输出目录为止
+fn remove_empty_parents(output_dir: &Path, path: &Path) {
+    let mut dir = path.parent();
+    while let Some(d) = dir {
+        if d == output_dir || !d.starts_with(output_dir) {
+            break;
+        }
+        // 目录非空时 remove_dir 失败，停止向上清理
+        if std::fs::remove_dir(d).is_err() {
+            break;
+        }
+        dir = d.parent();
+    }
+}
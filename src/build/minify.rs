@@ -0,0 +1,487 @@
+//! 输出阶段的 HTML / CSS / JS 压缩
+//!
+//! 只做不改变语义的保守压缩：去除注释、折叠空白。HTML 中 `<pre>`、`<textarea>`
+//! 与内联 `<script>` 的内容原样保留；JS 保留换行以免影响自动分号插入。
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 各类文件压缩前后的字节数，渲染阶段会在多个线程中同时累计
+#[derive(Debug, Default)]
+pub struct Savings {
+    html: Counter,
+    css: Counter,
+    js: Counter,
+}
+
+#[derive(Debug, Default)]
+struct Counter {
+    files: AtomicUsize,
+    before: AtomicUsize,
+    after: AtomicUsize,
+}
+
+impl Counter {
+    fn record(&self, before: usize, after: usize) {
+        self.files.fetch_add(1, Ordering::Relaxed);
+        self.before.fetch_add(before, Ordering::Relaxed);
+        self.after.fetch_add(after, Ordering::Relaxed);
+    }
+
+    fn summary(&self, kind: &str) -> Option<String> {
+        let files = self.files.load(Ordering::Relaxed);
+        if files == 0 {
+            return None;
+        }
+        let before = self.before.load(Ordering::Relaxed);
+        let after = self.after.load(Ordering::Relaxed);
+        let saved = before.saturating_sub(after);
+        let percent = if before > 0 {
+            saved as f64 * 100.0 / before as f64
+        } else {
+            0.0
+        };
+        Some(format!("{kind} {files} 个文件节省 {saved} 字节（{percent:.1}%）"))
+    }
+}
+
+impl Savings {
+    pub fn html(&self, input: &str) -> String {
+        let output = html(input);
+        self.html.record(input.len(), output.len());
+        output
+    }
+
+    pub fn css(&self, input: &str) -> String {
+        let output = css(input);
+        self.css.record(input.len(), output.len());
+        output
+    }
+
+    pub fn js(&self, input: &str) -> String {
+        let output = js(input);
+        self.js.record(input.len(), output.len());
+        output
+    }
+
+    /// 输出压缩统计日志；本次构建没有压缩任何文件时不输出
+    pub fn log(&self) {
+        let parts: Vec<String> = [
+            self.html.summary("HTML"),
+            self.css.summary("CSS"),
+            self.js.summary("JS"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !parts.is_empty() {
+            tracing::info!("压缩完成：{}", parts.join("，"));
+        }
+    }
+}
+
+/// 内容需原样保留的元素，其中 style 的内容按 CSS 压缩
+const RAW_TEXT_ELEMENTS: &[&str] = &["pre", "textarea", "script", "style"];
+
+/// 块级及不参与行内排版的元素，其前后的空白可以整体去除
+const BLOCK_ELEMENTS: &[&str] = &[
+    "!doctype", "address", "article", "aside", "base", "blockquote", "body", "br", "dd", "details",
+    "dialog", "div", "dl", "dt", "fieldset", "figcaption", "figure", "footer", "form", "h1", "h2",
+    "h3", "h4", "h5", "h6", "head", "header", "hr", "html", "li", "link", "main", "meta", "nav",
+    "noscript", "ol", "optgroup", "option", "p", "script", "section", "style", "summary", "table",
+    "tbody", "td", "template", "tfoot", "th", "thead", "title", "tr", "ul",
+];
+
+/// 压缩 HTML：去除注释（保留 IE 条件注释），折叠文本中的连续空白，去除块级元素之间的空白
+pub fn html(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    let mut pending_space = false;
+    // 上一个输出的是块级标签时，其后的空白不影响渲染
+    let mut after_block = true;
+
+    while !rest.is_empty() {
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            let end = comment.find("-->").map_or(rest.len(), |i| i + 4 + 3);
+            if comment.starts_with("[if") {
+                out.push_str(&rest[..end]);
+            }
+            rest = &rest[end..];
+            continue;
+        }
+
+        if starts_tag(rest) {
+            let end = tag_end(rest);
+            let tag = &rest[..end];
+            let name = tag_name(tag);
+            let closing = tag.starts_with("</");
+            let block = BLOCK_ELEMENTS.contains(&name.as_str());
+
+            if pending_space && !after_block && !block {
+                out.push(' ');
+            }
+            pending_space = false;
+            out.push_str(tag);
+            after_block = block;
+            rest = &rest[end..];
+
+            if !closing && !tag.ends_with("/>") && RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+                let content_end = find_closing_tag(rest, &name).unwrap_or(rest.len());
+                let content = &rest[..content_end];
+                if name == "style" {
+                    out.push_str(&css(content));
+                } else {
+                    out.push_str(content);
+                }
+                rest = &rest[content_end..];
+            }
+            continue;
+        }
+
+        // 文本：连续空白折叠为一个空格，延迟到下一个行内内容前输出
+        let text_end = next_tag_start(rest);
+        for c in rest[..text_end].chars() {
+            if c.is_whitespace() {
+                pending_space = true;
+            } else {
+                if pending_space && !after_block {
+                    out.push(' ');
+                }
+                pending_space = false;
+                after_block = false;
+                out.push(c);
+            }
+        }
+        rest = &rest[text_end..];
+    }
+
+    out
+}
+
+/// `<` 后紧跟字母、`/`、`!` 或 `?` 时才是标签，否则按普通文本处理
+fn starts_tag(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next() == Some('<')
+        && chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?'))
+}
+
+fn next_tag_start(s: &str) -> usize {
+    let mut offset = 0;
+    while let Some(i) = s[offset..].find('<') {
+        let pos = offset + i;
+        if pos > 0 && (starts_tag(&s[pos..]) || s[pos..].starts_with("<!--")) {
+            return pos;
+        }
+        offset = pos + 1;
+    }
+    s.len()
+}
+
+/// 标签结束位置（`>` 之后），跳过属性值引号内的 `>`
+fn tag_end(s: &str) -> usize {
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '>' => return i + 1,
+            None => {}
+        }
+    }
+    s.len()
+}
+
+fn tag_name(tag: &str) -> String {
+    tag.trim_start_matches('<')
+        .trim_start_matches('/')
+        .chars()
+        .take_while(|c| !c.is_whitespace() && *c != '>' && *c != '/')
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// 查找 `</name` 闭合标签的位置（不区分大小写）
+fn find_closing_tag(s: &str, name: &str) -> Option<usize> {
+    let needle = format!("</{name}");
+    let bytes = s.as_bytes();
+    (0..bytes.len()).find(|&i| {
+        bytes[i] == b'<'
+            && bytes.len() >= i + needle.len()
+            && bytes[i..i + needle.len()].eq_ignore_ascii_case(needle.as_bytes())
+    })
+}
+
+/// 压缩 CSS：去除注释（保留 `/*!` 版权注释），折叠空白，去除分隔符两侧的空白和末尾分号
+pub fn css(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let chars: Vec<char> = input.chars().collect();
+    let mut i = 0;
+    let mut pending_space = false;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '/' && chars.get(i + 1) == Some(&'*') {
+            let end = find_seq(&chars, i + 2, &['*', '/']).map_or(chars.len(), |e| e + 2);
+            if chars.get(i + 2) == Some(&'!') {
+                out.extend(&chars[i..end]);
+            }
+            i = end;
+            continue;
+        }
+        if c.is_whitespace() {
+            pending_space = true;
+            i += 1;
+            continue;
+        }
+        if c == '"' || c == '\'' {
+            let end = string_end(&chars, i);
+            flush_css_space(&mut out, &mut pending_space, c);
+            out.extend(&chars[i..end]);
+            i = end;
+            continue;
+        }
+        if c == '}' && out.ends_with(';') {
+            out.pop();
+        }
+        flush_css_space(&mut out, &mut pending_space, c);
+        out.push(c);
+        i += 1;
+    }
+
+    out.trim().to_owned()
+}
+
+/// 分隔符两侧的空白可去除。`:` 与 `(` 之前的空格在选择器和媒体查询中有意义，
+/// `+`、`-` 在 calc() 中需要空格，均保持原样
+fn flush_css_space(out: &mut String, pending_space: &mut bool, next: char) {
+    const NO_SPACE_BEFORE: &[char] = &['{', '}', ';', ',', '>', '~', ')'];
+    const NO_SPACE_AFTER: &[char] = &['{', '}', ';', ':', ',', '>', '~', '('];
+    if *pending_space
+        && !out.is_empty()
+        && !NO_SPACE_BEFORE.contains(&next)
+        && !out.ends_with(NO_SPACE_AFTER)
+    {
+        out.push(' ');
+    }
+    *pending_space = false;
+}
+
+fn find_seq(chars: &[char], from: usize, seq: &[char]) -> Option<usize> {
+    (from..chars.len()).find(|&i| chars[i..].starts_with(seq))
+}
+
+/// 从引号位置开始，返回字符串字面量结束位置（结束引号之后）
+fn string_end(chars: &[char], start: usize) -> usize {
+    let quote = chars[start];
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            c if c == quote => return i + 1,
+            '\n' if quote != '`' => return i,
+            _ => i += 1,
+        }
+    }
+    chars.len()
+}
+
+/// 其后出现的 `/` 表示正则字面量而非除号的关键字
+const REGEX_PREFIX_KEYWORDS: &[&str] = &[
+    "return", "typeof", "instanceof", "in", "of", "new", "delete", "void", "throw", "case", "do",
+    "else", "yield", "await",
+];
+
+/// 压缩 JS：去除注释与多余空白，保留换行（避免改变自动分号插入的结果）；
+/// 字符串、模板字符串与正则字面量原样保留
+pub fn js(input: &str) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut out = String::with_capacity(input.len());
+    let mut i = 0;
+    let mut pending_space = false;
+    let mut pending_newline = false;
+    // 模板字符串中 `${` 所在的花括号深度，遇到对应的 `}` 时回到模板字符串
+    let mut template_stack: Vec<usize> = Vec::new();
+    let mut brace_depth = 0usize;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        if c == '/' && next == Some('/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+        if c == '/' && next == Some('*') {
+            let end = find_seq(&chars, i + 2, &['*', '/']).map_or(chars.len(), |e| e + 2);
+            if chars[i..end].contains(&'\n') {
+                pending_newline = true;
+            } else {
+                pending_space = true;
+            }
+            i = end;
+            continue;
+        }
+        if c == '\n' {
+            pending_newline = true;
+            i += 1;
+            continue;
+        }
+        if c.is_whitespace() {
+            pending_space = true;
+            i += 1;
+            continue;
+        }
+
+        flush_js_space(&mut out, &mut pending_space, &mut pending_newline, c);
+
+        match c {
+            '"' | '\'' => {
+                let end = string_end(&chars, i);
+                out.extend(&chars[i..end]);
+                i = end;
+            }
+            '`' => {
+                i = copy_template(&chars, i + 1, &mut out, &mut template_stack, brace_depth, '`');
+            }
+            '/' if regex_allowed(&out) => {
+                let end = regex_end(&chars, i);
+                out.extend(&chars[i..end]);
+                i = end;
+            }
+            '{' => {
+                brace_depth += 1;
+                out.push(c);
+                i += 1;
+            }
+            '}' if template_stack.last() == Some(&brace_depth) => {
+                template_stack.pop();
+                i = copy_template(&chars, i + 1, &mut out, &mut template_stack, brace_depth, '}');
+            }
+            '}' => {
+                brace_depth = brace_depth.saturating_sub(1);
+                out.push(c);
+                i += 1;
+            }
+            _ => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    out.trim().to_owned()
+}
+
+/// 复制模板字符串片段（从 `` ` `` 或 `}` 之后开始），遇到结束反引号或 `${` 时返回
+fn copy_template(
+    chars: &[char],
+    mut i: usize,
+    out: &mut String,
+    template_stack: &mut Vec<usize>,
+    brace_depth: usize,
+    opener: char,
+) -> usize {
+    out.push(opener);
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                out.extend(chars.get(i..i + 2).unwrap_or(&chars[i..]));
+                i += 2;
+            }
+            '`' => {
+                out.push('`');
+                return i + 1;
+            }
+            '$' if chars.get(i + 1) == Some(&'{') => {
+                out.push_str("${");
+                template_stack.push(brace_depth);
+                return i + 2;
+            }
+            c => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    i
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// 两个相邻字符合并后会改变词法含义时需要保留空格（标识符相连，或 `+ +`、`- -`）
+fn flush_js_space(out: &mut String, pending_space: &mut bool, pending_newline: &mut bool, next: char) {
+    if let Some(prev) = out.chars().last() {
+        if *pending_newline {
+            // 这些字符前后的换行不会影响自动分号插入，可以去除
+            if !matches!(prev, '{' | ';' | ',' | '(' | '[') && !matches!(next, '}' | ')' | ']' | ',' | ';') {
+                out.push('\n');
+            }
+        } else if *pending_space
+            && ((is_ident_char(prev) && is_ident_char(next))
+                || (prev == next && matches!(prev, '+' | '-'))
+                || (prev == '/' && next == '/'))
+        {
+            out.push(' ');
+        }
+    }
+    *pending_space = false;
+    *pending_newline = false;
+}
+
+/// 根据前一个有效词判断 `/` 是否开始正则字面量
+fn regex_allowed(out: &str) -> bool {
+    let trimmed = out.trim_end();
+    let Some(prev) = trimmed.chars().last() else {
+        return true;
+    };
+    if prev == ')' || prev == ']' {
+        return false;
+    }
+    if is_ident_char(prev) {
+        let word: String = trimmed
+            .chars()
+            .rev()
+            .take_while(|c| is_ident_char(*c))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect();
+        return REGEX_PREFIX_KEYWORDS.contains(&word.as_str());
+    }
+    true
+}
+
+/// 正则字面量结束位置（含标志位），字符类 `[...]` 中的 `/` 不结束正则
+fn regex_end(chars: &[char], start: usize) -> usize {
+    let mut i = start + 1;
+    let mut in_class = false;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            '[' => {
+                in_class = true;
+                i += 1;
+            }
+            ']' => {
+                in_class = false;
+                i += 1;
+            }
+            '/' if !in_class => {
+                i += 1;
+                while i < chars.len() && chars[i].is_ascii_alphabetic() {
+                    i += 1;
+                }
+                return i;
+            }
+            '\n' => return i,
+            _ => i += 1,
+        }
+    }
+    chars.len()
+}
@@ -1,22 +1,63 @@
+use crate::build::events::EventSink;
 use crate::build::graph::DepGraph;
 use crate::build::incremental::{BuildStats, HashCache};
+use crate::build::manifest::{self, OutputManifest};
+use crate::build::minify::Savings;
+use crate::build::profile::Profiler;
 use crate::build::stages;
-use crate::build::stages::load::DbPost;
+use crate::build::routes::UrlResolver;
+use crate::build::stages::load::{DbCategory, DbMedia, DbPage, DbPost, DbTag};
+use crate::build::BuildParams;
 use crate::config::SiteConfig;
+use crate::theme::config::SpecialPage;
 use crate::admin::settings::SiteSettings;
 use anyhow::Result;
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// 增量渲染范围
+struct RebuildScope {
+    /// 无论依赖是否变化都需重建的页面 URL（独立页面变更、模板变更）
+    urls: HashSet<String>,
+    /// 内容变更或已删除的文章依赖键（`post:{slug}`）
+    posts: HashSet<String>,
+    /// 主题资源是否变更
+    assets: bool,
+}
 
 /// 构建管道上下文，聚合构建所需的全部参数
 struct BuildContext<'a> {
     project_root: &'a Path,
     config: &'a SiteConfig,
+    /// 本次构建的写入目录（暂存目录）
+    output_dir: &'a Path,
     plugin_configs: &'a HashMap<String, HashMap<String, serde_json::Value>>,
     theme_saved_config: &'a HashMap<String, serde_json::Value>,
     site_settings: &'a SiteSettings,
+    db_pages: &'a [DbPage],
+    db_categories: &'a [DbCategory],
+    db_tags: &'a [DbTag],
+    db_media: &'a [DbMedia],
     hash_cache: &'a mut HashCache,
     dep_graph: &'a mut DepGraph,
+    manifest: &'a mut OutputManifest,
+    events: &'a EventSink,
+    profiler: &'a Profiler,
+}
+
+impl BuildContext<'_> {
+    /// 推送阶段开始事件，返回计时起点
+    fn stage_begin(&self, stage: &str) -> Instant {
+        self.events.stage_begin(stage);
+        Instant::now()
+    }
+
+    /// 记录阶段耗时并推送阶段结束事件
+    fn stage_end(&self, stage: &str, started: Instant) {
+        self.profiler.record_stage(stage, started.elapsed());
+        self.events.stage_end(stage);
+    }
 }
 
 fn serialize_posts(posts: &[crate::content::Post]) -> serde_json::Value {
@@ -27,15 +68,18 @@ fn serialize_posts(posts: &[crate::content::Post]) -> serde_json::Value {
                 "id": p.id.to_string(),
                 "slug": &p.slug,
                 "title": &p.title,
-                "url": format!("/posts/{}/", p.slug),
+                "url": &p.url,
                 "content": p.content.html(),
-                "tags": &p.tags,
-                "category": &p.category,
+                "tags": p.tag_names(),
+                "category": p.category_name(),
+                "tag_terms": &p.tags,
+                "category_term": &p.category,
                 "excerpt": &p.excerpt,
                 "created_at": p.created_at.to_rfc3339(),
                 "updated_at": p.updated_at.to_rfc3339(),
                 "toc": &p.toc,
                 "cover_image": &p.cover_image,
+                "noindex": p.noindex,
                 "author": &p.author,
                 "reading_time": p.reading_time,
                 "word_count": p.word_count,
@@ -44,33 +88,254 @@ fn serialize_posts(posts: &[crate::content::Post]) -> serde_json::Value {
         .collect::<Vec<_>>())
 }
 
-/// 计算单篇文章的内容哈希（基于 slug + content + updated_at + meta 字段）
+/// 计算单篇文章的内容哈希（基于 slug + content + updated_at + meta 字段及标签/分类关联）
 fn compute_post_hash(db_post: &DbPost) -> String {
+    let fingerprint = format!(
+        "{}|{}|{}|{}|{}|{}|{}",
+        db_post.slug,
+        db_post.title,
+        db_post.content,
+        db_post.updated_at,
+        db_post.meta,
+        db_post.tag_ids.join(","),
+        db_post.category_id.as_deref().unwrap_or("")
+    );
+    HashCache::hash_bytes(fingerprint.as_bytes())
+}
+
+/// 计算单个独立页面的内容哈希（基于 slug + title + content + updated_at + template）
+fn compute_page_hash(db_page: &DbPage) -> String {
     let fingerprint = format!(
         "{}|{}|{}|{}|{}",
-        db_post.slug, db_post.title, db_post.content, db_post.updated_at, db_post.meta
+        db_page.slug,
+        db_page.title,
+        db_page.content,
+        db_page.updated_at,
+        db_page.template.as_deref().unwrap_or("")
     );
     HashCache::hash_bytes(fingerprint.as_bytes())
 }
 
+/// 计算分类表的整体哈希：分类名、slug、描述或层级变化都会影响文章 URL 和分类页
+fn compute_categories_hash(db_categories: &[DbCategory]) -> String {
+    let mut rows: Vec<String> = db_categories
+        .iter()
+        .map(|c| {
+            format!(
+                "{}|{}|{}|{}|{}",
+                c.id,
+                c.name,
+                c.slug,
+                c.description,
+                c.parent_id.as_deref().unwrap_or("")
+            )
+        })
+        .collect();
+    rows.sort();
+    HashCache::hash_bytes(rows.join("\n").as_bytes())
+}
+
+/// 计算标签表的整体哈希：标签改名、slug 或描述变化会影响所有引用它的文章
+fn compute_tags_hash(db_tags: &[DbTag]) -> String {
+    let mut rows: Vec<String> = db_tags
+        .iter()
+        .map(|t| format!("{}|{}|{}|{}", t.id, t.name, t.slug, t.description))
+        .collect();
+    rows.sort();
+    HashCache::hash_bytes(rows.join("\n").as_bytes())
+}
+
+/// 计算主题继承链上各 assets/ 目录的整体哈希（相对路径与文件内容），用于发现样式、脚本等资源的变更
+fn compute_theme_assets_hash(project_root: &Path, config: &SiteConfig) -> String {
+    fn walk(base: &Path, dir: &Path, prefix: &str, rows: &mut Vec<String>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                walk(base, &path, prefix, rows);
+            } else if let (Ok(rel), Ok(hash)) = (path.strip_prefix(base), HashCache::compute_hash(&path)) {
+                rows.push(format!("{prefix}/{}|{hash}", rel.display()));
+            }
+        }
+    }
+
+    let chain = crate::theme::config::resolve_theme(project_root, &config.theme.active)
+        .map(|resolved| resolved.parent_chain)
+        .unwrap_or_else(|_| vec![config.theme.active.clone()]);
+    let mut rows = Vec::new();
+    for theme in &chain {
+        let assets_dir = project_root.join("themes").join(theme).join("assets");
+        walk(&assets_dir, &assets_dir, theme, &mut rows);
+    }
+    rows.sort();
+    HashCache::hash_bytes(rows.join("\n").as_bytes())
+}
+
+/// 计算媒体目录的文件签名（相对路径、大小与修改时间），用于发现需要重新发布的媒体文件
+fn compute_media_hash(project_root: &Path, config: &SiteConfig) -> String {
+    fn walk(base: &Path, dir: &Path, rows: &mut Vec<String>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                walk(base, &path, rows);
+            } else if let (Ok(rel), Ok(meta)) = (path.strip_prefix(base), entry.metadata()) {
+                let modified = meta
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                    .map_or(0, |d| d.as_nanos());
+                rows.push(format!("{}|{}|{modified}", rel.display(), meta.len()));
+            }
+        }
+    }
+
+    let media_dir = project_root.join(&config.media.upload_dir);
+    let mut rows = Vec::new();
+    walk(&media_dir, &media_dir, &mut rows);
+    rows.sort();
+    HashCache::hash_bytes(rows.join("\n").as_bytes())
+}
+
+/// 按 [routes] 配置创建 URL 解析器，并登记标签表和分类表中存储的 slug
+fn url_resolver(bctx: &BuildContext<'_>) -> UrlResolver {
+    resolver_for(bctx.config, bctx.db_tags, bctx.db_categories)
+}
+
+fn resolver_for(config: &SiteConfig, db_tags: &[DbTag], db_categories: &[DbCategory]) -> UrlResolver {
+    UrlResolver::new(&config.routes)
+        .with_tag_slugs(db_tags.iter().map(|t| (t.name.as_str(), t.slug.as_str())))
+        .with_category_slugs(db_categories.iter().map(|c| (c.name.as_str(), c.slug.as_str())))
+}
+
+/// 读取当前主题（含继承链）在 theme.toml 中声明的特殊页面
+fn load_special_pages(
+    project_root: &Path,
+    config: &SiteConfig,
+    events: &EventSink,
+) -> Vec<SpecialPage> {
+    match crate::theme::config::resolve_theme(project_root, &config.theme.active) {
+        Ok(resolved) => resolved.special_pages,
+        Err(e) => {
+            events.warn(format!("解析主题特殊页面失败：{e}"));
+            Vec::new()
+        }
+    }
+}
+
+/// 已启用插件在 `capabilities.generates` 中声明的输出路径
+///
+/// 声明路径相对项目根目录（如 `public/robots.txt`），位于配置输出目录下的路径映射到本次的写入目录
+fn plugin_generated_paths(
+    project_root: &Path,
+    output_dir: &Path,
+    config: &SiteConfig,
+) -> Vec<PathBuf> {
+    let plugins_dir = project_root.join("plugins");
+    config
+        .plugins
+        .enabled
+        .iter()
+        .filter_map(|name| {
+            let toml_path = plugins_dir.join(name).join("plugin.toml");
+            crate::plugin::registry::load_plugin_info(&toml_path).ok()
+        })
+        .flat_map(|info| info.capabilities.generates)
+        .map(|path| match Path::new(&path).strip_prefix(&config.build.output_dir) {
+            Ok(rest) => output_dir.join(rest),
+            Err(_) => project_root.join(path),
+        })
+        .collect()
+}
+
+/// 调用插件钩子，前后推送 `hook:{name}` 阶段事件，并记录每个插件处理器的耗时
+fn call_hook(
+    bctx: &BuildContext<'_>,
+    eng: &crate::lua::runtime::PluginEngine,
+    hook: &str,
+    ctx: &serde_json::Value,
+) -> Result<()> {
+    let stage = format!("hook:{hook}");
+    bctx.events.stage_begin(&stage);
+    for (plugin, elapsed) in eng.hooks.call_action_timed(&eng.lua, hook, ctx)? {
+        bctx.profiler.record_hook(hook, &plugin, elapsed);
+    }
+    bctx.events.stage_end(&stage);
+    Ok(())
+}
+
+/// 本次构建更新后的增量缓存：哈希缓存、依赖图和输出清单
+///
+/// 缓存描述的是暂存目录中的输出，须在输出切换上线后才保存，
+/// 否则切换失败时下次构建会误判内容未变更而跳过切换
+pub struct BuildCaches {
+    hash_cache: HashCache,
+    dep_graph: DepGraph,
+    manifest: OutputManifest,
+}
+
+impl BuildCaches {
+    /// 持久化全部缓存，单项失败只发出警告
+    pub fn save(&self, events: &EventSink) {
+        if let Err(e) = self.hash_cache.save() {
+            events.warn(format!("保存哈希缓存失败：{e}"));
+        }
+        if let Err(e) = self.dep_graph.save() {
+            events.warn(format!("保存依赖图失败：{e}"));
+        }
+        if let Err(e) = self.manifest.save() {
+            events.warn(format!("保存输出清单失败：{e}"));
+        }
+    }
+
+    /// 删除缓存目录中的哈希缓存、依赖图和输出清单，下次构建全量重建
+    pub fn discard(cache_dir: &Path) {
+        for name in ["hashes.json", "deps.json", manifest::FILE_NAME] {
+            if let Err(e) = std::fs::remove_file(cache_dir.join(name))
+                && e.kind() != std::io::ErrorKind::NotFound
+            {
+                tracing::warn!("删除缓存文件 {name} 失败：{e}");
+            }
+        }
+    }
+}
+
 /// 执行构建管道，支持增量构建
 ///
-/// `force` 为 true 时跳过增量判断，执行全量重建
+/// 页面与资源写入 `output_dir`（暂存目录），`force` 为 true 时跳过增量判断，执行全量重建。
+/// 返回的缓存由调用方在输出切换成功后保存；构建失败时缓存不变，下次构建仍按失败前的状态判断变更
 pub fn execute(
     project_root: &Path,
     config: &SiteConfig,
-    plugin_configs: HashMap<String, HashMap<String, serde_json::Value>>,
-    theme_saved_config: HashMap<String, serde_json::Value>,
-    db_posts: Vec<DbPost>,
-    site_settings: SiteSettings,
+    output_dir: &Path,
+    params: BuildParams,
     force: bool,
-) -> Result<BuildStats> {
+) -> Result<(BuildStats, BuildCaches)> {
+    let BuildParams {
+        plugin_configs,
+        theme_saved_config,
+        db_posts,
+        db_pages,
+        db_categories,
+        db_tags,
+        db_media,
+        site_settings,
+        events,
+        ..
+    } = params;
+
     tracing::info!("开始构建...");
     let start = std::time::Instant::now();
 
     let cache_dir = project_root.join(&config.build.cache_dir);
     let mut hash_cache = HashCache::load(&cache_dir);
     let mut dep_graph = DepGraph::load(&cache_dir);
+    let mut output_manifest = OutputManifest::load(&cache_dir);
+    let profiler = Profiler::default();
 
     // 判断是否需要全量重建
     let force_full = force || should_full_rebuild(&hash_cache, project_root, config);
@@ -86,25 +351,34 @@ pub fn execute(
     let mut bctx = BuildContext {
         project_root,
         config,
+        output_dir,
         plugin_configs: &plugin_configs,
         theme_saved_config: &theme_saved_config,
         site_settings: &site_settings,
+        db_pages: &db_pages,
+        db_categories: &db_categories,
+        db_tags: &db_tags,
+        db_media: &db_media,
         hash_cache: &mut hash_cache,
         dep_graph: &mut dep_graph,
+        manifest: &mut output_manifest,
+        events: &events,
+        profiler: &profiler,
     };
 
     // 尝试增量构建，失败时自动回退到全量
-    let result = if force_full {
+    let mut stats = if force_full {
         full_build(&mut bctx, db_posts)
     } else {
         match incremental_build(&mut bctx, &db_posts) {
             Ok(stats) => Ok(stats),
             Err(e) => {
-                tracing::warn!("增量构建失败，回退到全量重建：{e}");
+                events.warn(format!("增量构建失败，回退到全量重建：{e}"));
                 full_build(&mut bctx, db_posts)
             }
         }
-    };
+    }?;
+    stats.profile = profiler.finish();
 
     // 构建完成后更新配置文件和模板哈希
     let config_path = project_root.join("cblog.toml");
@@ -117,29 +391,106 @@ pub fn execute(
     let themes_dir = project_root.join("themes");
     hash_cache.update_templates(&themes_dir, &config.theme.active);
 
-    if let Err(e) = hash_cache.save() {
-        tracing::warn!("保存哈希缓存失败：{}", e);
-    }
-    if let Err(e) = dep_graph.save() {
-        tracing::warn!("保存依赖图失败：{}", e);
+    let variant = build_variant(config);
+    if variant.is_empty() {
+        hash_cache.remove(VARIANT_KEY);
+    } else {
+        hash_cache.update(VARIANT_KEY.to_owned(), variant);
     }
 
     let duration = start.elapsed();
-    if let Ok(ref stats) = result {
-        tracing::info!(
-            "构建完成，耗时 {:.2}s（共 {} 页，重建 {}，缓存 {}）",
-            duration.as_secs_f64(),
-            stats.total_pages,
-            stats.rebuilt,
-            stats.cached,
-        );
+    tracing::info!(
+        "构建完成，耗时 {:.2}s（共 {} 页，重建 {}，缓存 {}，清理 {} 个过期文件）",
+        duration.as_secs_f64(),
+        stats.total_pages,
+        stats.rebuilt,
+        stats.cached,
+        stats.pruned,
+    );
+    if !stats.render_errors.is_empty() {
+        events.warn(format!("{} 个页面渲染失败", stats.render_errors.len()));
     }
 
-    result
+    let caches = BuildCaches {
+        hash_cache,
+        dep_graph,
+        manifest: output_manifest,
+    };
+    Ok((stats, caches))
+}
+
+/// 在内存中渲染单篇文章用于预览：走 load → generate → render 流程但不写入输出目录、不调用插件
+///
+/// `params.db_posts` 只需包含待预览的文章（状态视为已发布）及其前后相邻的已发布文章，
+/// 仅生成该文章一个页面，不加载整站内容
+pub fn render_post_preview(
+    project_root: &Path,
+    config: &SiteConfig,
+    params: &BuildParams,
+    post_id: &str,
+) -> Result<String> {
+    let resolver = resolver_for(config, &params.db_tags, &params.db_categories);
+    let posts = stages::load::load_posts_from_db(
+        params.db_posts.clone(),
+        config,
+        &resolver,
+        &params.db_tags,
+        &params.db_categories,
+    );
+    let Some(index) = posts.iter().position(|p| p.id.to_string() == post_id) else {
+        anyhow::bail!("文章不存在：{post_id}");
+    };
+    let page = stages::generate::post_page(&posts, index);
+
+    let output_dir = project_root.join(&config.build.output_dir);
+    let assets = stages::assets::AssetManifest::load(&output_dir);
+    let images = stages::images::ResponsiveImages::load(&output_dir);
+    let renderer = stages::render::PageRenderer::new(
+        project_root,
+        config,
+        &resolver,
+        &params.theme_saved_config,
+        &params.site_settings,
+        &assets,
+        &images,
+    )?;
+    renderer.render(&page).map_err(|e| anyhow::anyhow!("{e}"))
+}
+
+/// 哈希缓存中记录上次构建所用运行时选项的键，默认选项构建时不记录
+const VARIANT_KEY: &str = "build_variant";
+
+/// 命令行与开发服务器在运行时附加的构建选项，任一项与上次构建不同时已有页面都不可复用
+fn build_variant(config: &SiteConfig) -> String {
+    let build = &config.build;
+    let mut parts = Vec::new();
+    if build.live_reload {
+        parts.push("live_reload".to_owned());
+    }
+    if build.drafts {
+        parts.push("drafts".to_owned());
+    }
+    if build.future {
+        parts.push("future".to_owned());
+    }
+    if let Some(url) = &build.base_url {
+        parts.push(format!("base_url={url}"));
+    }
+    // 指定配置文件时 cblog.toml 的变更检测不再适用，改为记录该文件的内容哈希
+    if let Some(path) = &build.config_file {
+        let hash = HashCache::compute_hash(path).unwrap_or_default();
+        parts.push(format!("config={hash}"));
+    }
+    parts.join(";")
 }
 
 /// 判断是否需要全量重建
 fn should_full_rebuild(hash_cache: &HashCache, project_root: &Path, config: &SiteConfig) -> bool {
+    if hash_cache.get(VARIANT_KEY).unwrap_or_default() != build_variant(config) {
+        tracing::info!("构建选项已变更，将执行全量重建");
+        return true;
+    }
+
     match hash_cache.config_changed(project_root) {
         Ok(true) => {
             tracing::info!("cblog.toml 已变更，将执行全量重建");
@@ -171,22 +522,36 @@ fn should_full_rebuild(hash_cache: &HashCache, project_root: &Path, config: &Sit
         }
     }
 
-    let output_dir = project_root.join(&config.build.output_dir);
-    if !output_dir.exists() {
-        tracing::info!("输出目录不存在，将执行全量重建");
-        return true;
-    }
-
     false
 }
 
 /// 全量重建
 fn full_build(bctx: &mut BuildContext<'_>, db_posts: Vec<DbPost>) -> Result<BuildStats> {
+    bctx.hash_cache.retain_prefixed("post:", |_| false);
     for db_post in &db_posts {
         let post_key = format!("post:{}", db_post.slug);
         let post_hash = compute_post_hash(db_post);
         bctx.hash_cache.update(post_key, post_hash);
     }
+    bctx.hash_cache.retain_prefixed("page:", |_| false);
+    for db_page in bctx.db_pages {
+        bctx.hash_cache
+            .update(format!("page:{}", db_page.slug), compute_page_hash(db_page));
+    }
+    bctx.hash_cache.update(
+        "categories".to_owned(),
+        compute_categories_hash(bctx.db_categories),
+    );
+    bctx.hash_cache
+        .update("tags".to_owned(), compute_tags_hash(bctx.db_tags));
+    bctx.hash_cache.update(
+        "theme_assets".to_owned(),
+        compute_theme_assets_hash(bctx.project_root, bctx.config),
+    );
+    bctx.hash_cache.update(
+        "media".to_owned(),
+        compute_media_hash(bctx.project_root, bctx.config),
+    );
 
     let theme_toml = bctx.project_root
         .join("themes")
@@ -210,124 +575,212 @@ fn incremental_build(
 
     let changed_templates = bctx.hash_cache.changed_templates(&themes_dir, &bctx.config.theme.active);
 
-    let mut changed_post_slugs: HashSet<String> = HashSet::new();
-    let mut any_post_changed = false;
-
+    // 变更或删除的文章，由 run_pipeline 按页面依赖键找出受影响的页面
+    let mut changed_posts: HashSet<String> = HashSet::new();
     for db_post in db_posts {
-        let post_key = format!("post:{}", db_post.slug);
+        let post_key = stages::generate::post_dep(&db_post.slug);
         let post_hash = compute_post_hash(db_post);
         if bctx.hash_cache.has_changed(&post_key, &post_hash) {
-            changed_post_slugs.insert(db_post.slug.clone());
-            any_post_changed = true;
+            changed_posts.insert(post_key.clone());
         }
         bctx.hash_cache.update(post_key, post_hash);
     }
 
-    // 检查文章数量变化（新增或删除文章）
-    let current_slugs: HashSet<String> = db_posts.iter().map(|p| p.slug.clone()).collect();
-    let cached_post_keys: Vec<String> = bctx.hash_cache.cached_post_keys();
-    for key in &cached_post_keys {
-        let slug = key.strip_prefix("post:").unwrap_or(key);
-        if !current_slugs.contains(slug) {
-            any_post_changed = true;
-            break;
+    let current_slugs: HashSet<&str> = db_posts.iter().map(|p| p.slug.as_str()).collect();
+    bctx.hash_cache.retain_prefixed("post:", |slug| {
+        let keep = current_slugs.contains(slug);
+        if !keep {
+            changed_posts.insert(stages::generate::post_dep(slug));
         }
+        keep
+    });
+
+    // 分类表/标签表变更会影响文章 URL、面包屑和所有标签/分类页，全量渲染
+    let mut taxonomy_changed = false;
+    let categories_hash = compute_categories_hash(bctx.db_categories);
+    if bctx.hash_cache.has_changed("categories", &categories_hash) {
+        taxonomy_changed = true;
     }
+    bctx.hash_cache.update("categories".to_owned(), categories_hash);
 
-    if !any_post_changed && changed_templates.is_empty() {
+    let tags_hash = compute_tags_hash(bctx.db_tags);
+    if bctx.hash_cache.has_changed("tags", &tags_hash) {
+        taxonomy_changed = true;
+    }
+    bctx.hash_cache.update("tags".to_owned(), tags_hash);
+
+    // 主题样式、脚本或媒体文件变更只需重新处理资源（开启指纹时由资源清单的变化触发页面重建）
+    let assets_hash = compute_theme_assets_hash(bctx.project_root, bctx.config);
+    let media_hash = compute_media_hash(bctx.project_root, bctx.config);
+    let assets_changed = bctx.hash_cache.has_changed("theme_assets", &assets_hash)
+        || bctx.hash_cache.has_changed("media", &media_hash);
+    bctx.hash_cache.update("theme_assets".to_owned(), assets_hash);
+    bctx.hash_cache.update("media".to_owned(), media_hash);
+
+    // 独立页面只影响自身 URL 与 sitemap，变更时仅重建对应页面
+    let resolver = url_resolver(bctx);
+    let mut changed_page_urls: HashSet<String> = HashSet::new();
+    let mut any_page_removed = false;
+    for db_page in bctx.db_pages {
+        let page_key = format!("page:{}", db_page.slug);
+        let page_hash = compute_page_hash(db_page);
+        if bctx.hash_cache.has_changed(&page_key, &page_hash) {
+            changed_page_urls.insert(resolver.page(&db_page.slug));
+        }
+        bctx.hash_cache.update(page_key, page_hash);
+    }
+    let current_page_slugs: HashSet<&str> =
+        bctx.db_pages.iter().map(|p| p.slug.as_str()).collect();
+    bctx.hash_cache.retain_prefixed("page:", |slug| {
+        let keep = current_page_slugs.contains(slug);
+        any_page_removed |= !keep;
+        keep
+    });
+
+    if changed_posts.is_empty()
+        && !taxonomy_changed
+        && !any_page_removed
+        && changed_page_urls.is_empty()
+        && changed_templates.is_empty()
+        && !assets_changed
+    {
         tracing::info!("无内容变更，跳过构建");
-        let posts = stages::load::load_posts_from_db(db_posts.to_vec(), bctx.config);
-        let taxonomy = stages::taxonomy::build_taxonomy(&posts, bctx.config);
-        let pages = stages::generate::generate_pages(&posts, &taxonomy, bctx.config);
+        let posts = stages::load::load_posts_from_db(
+            db_posts.to_vec(),
+            bctx.config,
+            &resolver,
+            bctx.db_tags,
+            bctx.db_categories,
+        );
+        let standalone = stages::load::load_pages_from_db(bctx.db_pages.to_vec(), bctx.config);
+        let taxonomy =
+            stages::taxonomy::build_taxonomy(&posts, bctx.db_categories, bctx.config);
+        let special = load_special_pages(bctx.project_root, bctx.config, bctx.events);
+        let templates =
+            stages::render::theme_template_names(bctx.project_root, &bctx.config.theme.active);
+        let pages = stages::generate::generate_pages(
+            &posts,
+            &standalone,
+            &special,
+            &taxonomy,
+            &templates,
+            bctx.config,
+        );
         return Ok(BuildStats {
             total_pages: pages.len(),
             rebuilt: 0,
             cached: pages.len(),
+            ..Default::default()
         });
     }
 
-    let mut urls_to_rebuild: HashSet<String> = HashSet::new();
-
-    if any_post_changed {
-        for slug in &changed_post_slugs {
-            urls_to_rebuild.insert(format!("/posts/{slug}/"));
-        }
+    // 页面删除后需要全量渲染以清理导航等引用
+    if taxonomy_changed || any_page_removed {
+        return run_pipeline(bctx, db_posts.to_vec(), None);
     }
 
-    // 模板变更 → 通过 DepGraph 找到使用该模板的所有页面
+    let mut urls_to_rebuild: HashSet<String> = changed_page_urls;
+
+    // 模板变更 → 通过 DepGraph 找到使用该模板的所有页面；
+    // 没有页面直接使用的模板（基础布局、partials）影响范围未知，全量渲染
     for template_name in &changed_templates {
         let tpl_key = template_name.strip_suffix(".cbtml").unwrap_or(template_name);
-        for url in bctx.dep_graph.get_affected_pages(tpl_key) {
-            urls_to_rebuild.insert(url.to_owned());
+        let affected = bctx.dep_graph.get_affected_pages(tpl_key);
+        if affected.is_empty() {
+            tracing::info!("模板 {tpl_key} 被其他模板引用，将全量渲染");
+            return run_pipeline(bctx, db_posts.to_vec(), None);
         }
+        urls_to_rebuild.extend(affected.into_iter().map(str::to_owned));
     }
 
-    // 文章内容变更的连锁影响太广（列表排序、分页、标签统计等），全量渲染
-    let rebuild_filter = if any_post_changed {
-        None
-    } else {
-        Some(urls_to_rebuild)
+    let scope = RebuildScope {
+        urls: urls_to_rebuild,
+        posts: changed_posts,
+        assets: assets_changed,
     };
-
-    run_pipeline(bctx, db_posts.to_vec(), rebuild_filter)
+    run_pipeline(bctx, db_posts.to_vec(), Some(scope))
 }
 
 /// 执行完整管道，可选地仅渲染指定 URL 的页面
 fn run_pipeline(
     bctx: &mut BuildContext<'_>,
     db_posts: Vec<DbPost>,
-    rebuild_urls: Option<HashSet<String>>,
+    scope: Option<RebuildScope>,
 ) -> Result<BuildStats> {
     let project_root = bctx.project_root;
     let config = bctx.config;
+    let events = bctx.events;
 
     // 初始化插件引擎
     const BUILTIN_FEATURES: &[&str] = &["image-optimize", "syntax-highlight", "toc", "search"];
     for plugin_name in &config.plugins.enabled {
         if BUILTIN_FEATURES.contains(&plugin_name.as_str()) {
-            tracing::warn!(
+            events.warn(format!(
                 "插件 '{plugin_name}' 已内置为核心功能，请从 [plugins] enabled 中移除。\
                 可通过 [features.{section}] enabled = false 禁用。",
                 section = plugin_name.replace('-', "_")
-            );
+            ));
         }
     }
 
     let engine = if !config.plugins.enabled.is_empty() {
+        let started = bctx.stage_begin("plugins");
         let ordered = crate::plugin::scheduler::resolve_load_order(
             project_root,
             &config.plugins.enabled,
         )?;
         let mut eng = crate::lua::runtime::PluginEngine::new(project_root, config, bctx.plugin_configs.clone())?;
         eng.load_plugins(&ordered)?;
+        bctx.stage_end("plugins", started);
         Some(eng)
     } else {
         None
     };
 
-    // Lua sandbox 只允许相对路径，传给插件的 output_dir 使用配置中的相对路径
-    let output_dir_str = config.build.output_dir.clone();
+    // Lua sandbox 只允许相对路径，传给插件的 output_dir 使用相对项目根目录的暂存路径
+    let output_dir = bctx.output_dir;
+    let output_dir_str = output_dir
+        .strip_prefix(project_root)
+        .unwrap_or(output_dir)
+        .to_string_lossy()
+        .to_string();
 
     // 阶段 1: content.load
-    let posts = stages::load::load_posts_from_db(db_posts, config);
-    tracing::info!("从数据库加载了 {} 篇文章", posts.len());
+    let started = bctx.stage_begin("load");
+    let resolver = url_resolver(bctx);
+    let posts = stages::load::load_posts_from_db(
+        db_posts,
+        config,
+        &resolver,
+        bctx.db_tags,
+        bctx.db_categories,
+    );
+    let standalone = stages::load::load_pages_from_db(bctx.db_pages.to_vec(), config);
+    tracing::info!(
+        "从数据库加载了 {} 篇文章，{} 个独立页面",
+        posts.len(),
+        standalone.len()
+    );
+    bctx.stage_end("load", started);
 
     if let Some(ref eng) = engine {
         let load_ctx = serde_json::json!({
             "project_root": project_root.to_string_lossy(),
             "posts": serialize_posts(&posts),
         });
-        eng.hooks.call_action(&eng.lua, "after_load", &load_ctx)?;
+        call_hook(bctx, eng, "after_load", &load_ctx)?;
     }
 
     // 阶段 3: taxonomy.build
-    let taxonomy = stages::taxonomy::build_taxonomy(&posts, config);
+    let started = bctx.stage_begin("taxonomy");
+    let taxonomy = stages::taxonomy::build_taxonomy(&posts, bctx.db_categories, config);
     tracing::info!(
         "分类索引：{} 个标签，{} 个分类，{} 个月份归档",
         taxonomy.tags.len(),
         taxonomy.categories.len(),
         taxonomy.archives.len()
     );
+    bctx.stage_end("taxonomy", started);
 
     if let Some(ref eng) = engine {
         let taxonomy_ctx = serde_json::json!({
@@ -337,21 +790,104 @@ fn run_pipeline(
             "category_count": taxonomy.categories.len(),
             "archive_count": taxonomy.archives.len(),
         });
-        eng.hooks.call_action(&eng.lua, "after_taxonomy", &taxonomy_ctx)?;
+        call_hook(bctx, eng, "after_taxonomy", &taxonomy_ctx)?;
     }
 
     // 阶段 4: page.generate
-    let pages = stages::generate::generate_pages(&posts, &taxonomy, config);
+    let started = bctx.stage_begin("generate");
+    let special = load_special_pages(project_root, config, events);
+    let templates = stages::render::theme_template_names(project_root, &config.theme.active);
+    let pages = stages::generate::generate_pages(
+        &posts,
+        &standalone,
+        &special,
+        &taxonomy,
+        &templates,
+        config,
+    );
     let total_pages = pages.len();
     tracing::info!("生成了 {} 个页面", total_pages);
+    bctx.stage_end("generate", started);
+
+    // 增量模式下需要重建的页面：显式指定的 URL、上次构建中不存在的页面、
+    // 依赖键变化（列表成员或顺序变化）的页面，以及引用了变更文章的页面
+    let rebuild_urls: Option<HashSet<String>> = scope.as_ref().map(|scope| {
+        pages
+            .iter()
+            .filter(|p| {
+                scope.urls.contains(&p.url)
+                    || match bctx.dep_graph.page_deps(&p.url) {
+                        None => true,
+                        Some(prev) => {
+                            prev != p.deps.as_slice()
+                                || p.deps.iter().any(|d| scope.posts.contains(d))
+                        }
+                    }
+            })
+            .map(|p| p.url.clone())
+            .collect()
+    });
 
     // 重建依赖图
     bctx.dep_graph.clear();
     for page in &pages {
         bctx.dep_graph.add_dependency(&page.template, &page.url);
+        bctx.dep_graph.set_page_deps(&page.url, &page.deps);
     }
 
-    // 阶段 5: page.render - 根据 rebuild_urls 过滤
+    // 阶段 5: asset.process - 在渲染前执行，asset_url() 需要本次的资源清单
+    let started = bctx.stage_begin("assets");
+    let minify = Savings::default();
+    let previous_assets = stages::assets::AssetManifest::load(output_dir);
+    let (asset_files, assets) = stages::assets::process_assets(
+        project_root,
+        output_dir,
+        config,
+        bctx.theme_saved_config,
+        &minify,
+    )?;
+    bctx.stage_end("assets", started);
+
+    if let Some(ref eng) = engine {
+        let assets_ctx = serde_json::json!({
+            "project_root": project_root.to_string_lossy(),
+            "output_dir": &output_dir_str,
+        });
+        call_hook(bctx, eng, "after_assets", &assets_ctx)?;
+    }
+
+    // 阶段 6: image.derive - 同样在渲染前执行，后处理需要本次的响应式图片清单
+    let started = bctx.stage_begin("images");
+    let previous_images = stages::images::ResponsiveImages::load(output_dir);
+    let (image_files, images) = stages::images::process_images(
+        project_root,
+        output_dir,
+        config,
+        &posts,
+        &standalone,
+        bctx.db_media,
+    )?;
+    bctx.stage_end("images", started);
+
+    // 资源文件名或已有图片的派生图变化后，所有引用它们的页面都需要重新渲染
+    let images_changed = images.changed_since(&previous_images);
+    let assets_changed = scope.as_ref().is_none_or(|s| s.assets)
+        || assets != previous_assets
+        || images != previous_images;
+    let rebuild_urls = if assets != previous_assets || images_changed {
+        if rebuild_urls.is_some() {
+            if images_changed {
+                tracing::info!("响应式图片已变更，重新渲染全部页面");
+            } else {
+                tracing::info!("资源指纹已变更，重新渲染全部页面");
+            }
+        }
+        None
+    } else {
+        rebuild_urls
+    };
+
+    // 阶段 7: page.render - 根据 rebuild_urls 过滤
     let (pages_to_render, cached) = match rebuild_urls {
         Some(ref urls) => {
             let filtered: Vec<_> = pages.iter().filter(|p| urls.contains(&p.url)).collect();
@@ -370,29 +906,74 @@ fn run_pipeline(
     };
 
     let rebuilt = pages_to_render.len();
-    stages::render::render_pages(project_root, config, &pages_to_render, bctx.theme_saved_config, bctx.site_settings)?;
-
-    if let Some(ref eng) = engine {
-        let render_ctx = serde_json::json!({
-            "project_root": project_root.to_string_lossy(),
-            "output_dir": &output_dir_str,
-        });
-        eng.hooks.call_action(&eng.lua, "after_render", &render_ctx)?;
+    let started = bctx.stage_begin("render");
+    let rctx = stages::render::RenderContext {
+        project_root,
+        output_dir,
+        config,
+        resolver: &resolver,
+        theme_config: bctx.theme_saved_config,
+        site_settings: bctx.site_settings,
+        events,
+        profiler: bctx.profiler,
+        minify: &minify,
+        assets: &assets,
+        images: &images,
+    };
+    let render_errors = stages::render::render_pages(&rctx, &pages_to_render)?;
+    bctx.stage_end("render", started);
+    minify.log();
+
+    if !render_errors.is_empty() {
+        if config.build.strict {
+            let details: Vec<String> = render_errors.iter().map(ToString::to_string).collect();
+            anyhow::bail!(
+                "严格模式下 {} 个页面渲染失败：\n{}",
+                render_errors.len(),
+                details.join("\n")
+            );
+        }
+        // 失败页面不记入依赖图，下次增量构建时重新渲染
+        for err in &render_errors {
+            bctx.dep_graph.remove_page(&err.url);
+        }
     }
 
-    // 阶段 6: asset.process
-    stages::assets::process_assets(project_root, config, bctx.theme_saved_config)?;
-
     if let Some(ref eng) = engine {
-        let assets_ctx = serde_json::json!({
+        let render_ctx = serde_json::json!({
             "project_root": project_root.to_string_lossy(),
             "output_dir": &output_dir_str,
         });
-        eng.hooks.call_action(&eng.lua, "after_assets", &assets_ctx)?;
+        call_hook(bctx, eng, "after_render", &render_ctx)?;
     }
 
-    // 阶段 7: build.finalize
-    stages::finalize::finalize(project_root, config, &posts)?;
+    let mut produced: Vec<PathBuf> = pages
+        .iter()
+        .map(|p| stages::render::page_output_path(output_dir, &p.url))
+        .collect();
+    produced.extend(asset_files);
+    produced.extend(image_files);
+
+    // 阶段 8: build.finalize
+    let started = bctx.stage_begin("finalize");
+    // 与页面渲染一致：后台设置的站点地址优先于 cblog.toml
+    let site_url = if bctx.site_settings.site_url.is_empty() {
+        &config.site.url
+    } else {
+        &bctx.site_settings.site_url
+    };
+    produced.extend(stages::finalize::finalize(&stages::finalize::FinalizeContext {
+        project_root,
+        output_dir,
+        config,
+        site_url: site_url.trim_end_matches('/'),
+        posts: &posts,
+        pages: &standalone,
+        rendered: &pages,
+        taxonomy: &taxonomy,
+        resolver: &resolver,
+    })?);
+    bctx.stage_end("finalize", started);
 
     if let Some(ref eng) = engine {
         let finalize_ctx = serde_json::json!({
@@ -401,13 +982,38 @@ fn run_pipeline(
             "posts": serialize_posts(&posts),
             "site_url": &config.site.url,
         });
-        eng.hooks
-            .call_action(&eng.lua, "after_finalize", &finalize_ctx)?;
+        call_hook(bctx, eng, "after_finalize", &finalize_ctx)?;
+    }
+
+    // 阶段 9: media.publish - 在页面与插件产物写出之后执行，referenced 模式需要扫描其中的媒体引用
+    let started = bctx.stage_begin("media");
+    produced.extend(stages::media::publish_media(
+        project_root,
+        output_dir,
+        config,
+        bctx.hash_cache,
+    )?);
+    bctx.stage_end("media", started);
+
+    // 清理上次构建产出、本次不再产出的文件（已删除/下线/改名的文章、消失的标签等）
+    let stale = bctx.manifest.replace(output_dir, &produced);
+    let pruned = manifest::prune(
+        output_dir,
+        &stale,
+        &plugin_generated_paths(project_root, output_dir, config),
+    );
+    if pruned > 0 {
+        tracing::info!("已清理 {} 个过期输出文件", pruned);
     }
 
     Ok(BuildStats {
         total_pages,
         rebuilt,
         cached,
+        pruned,
+        assets_changed,
+        render_errors,
+        // profile 由 execute 在管道结束后汇总填入
+        ..Default::default()
     })
 }
@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 最慢模板与最慢页面各保留的条数
+const SLOWEST_LIMIT: usize = 10;
+
+/// 构建性能剖析结果，以 JSON 保存在构建记录中
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BuildProfile {
+    pub stages: Vec<StageTiming>,
+    pub hooks: Vec<HookTiming>,
+    /// 按累计渲染耗时排序的最慢模板
+    pub templates: Vec<TemplateTiming>,
+    /// 按渲染耗时排序的最慢页面
+    pub pages: Vec<PageTiming>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageTiming {
+    pub name: String,
+    pub ms: f64,
+}
+
+/// 单个插件处理某个钩子的耗时
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookTiming {
+    pub hook: String,
+    pub plugin: String,
+    pub ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateTiming {
+    pub template: String,
+    pub pages: usize,
+    pub total_ms: f64,
+    pub max_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageTiming {
+    pub url: String,
+    pub template: String,
+    pub ms: f64,
+}
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+impl StageTiming {
+    pub fn new(name: &str, elapsed: Duration) -> Self {
+        Self {
+            name: name.to_owned(),
+            ms: millis(elapsed),
+        }
+    }
+}
+
+impl BuildProfile {
+    /// 追加一个阶段耗时（用于管道之外的暂存准备、输出切换等步骤）
+    pub fn push_stage(&mut self, name: &str, elapsed: Duration) {
+        self.stages.push(StageTiming::new(name, elapsed));
+    }
+
+    /// 格式化为命令行输出的文本表格
+    pub fn table(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "{}{}", pad("阶段", 32), align_right("耗时(ms)", 10));
+        for s in &self.stages {
+            let _ = writeln!(out, "{}{:>10.2}", pad(&s.name, 32), s.ms);
+        }
+        if !self.hooks.is_empty() {
+            let _ = writeln!(
+                out,
+                "\n{}{}{}",
+                pad("钩子", 20),
+                pad("插件", 24),
+                align_right("耗时(ms)", 10)
+            );
+            for h in &self.hooks {
+                let _ = writeln!(out, "{}{}{:>10.2}", pad(&h.hook, 20), pad(&h.plugin, 24), h.ms);
+            }
+        }
+        if !self.templates.is_empty() {
+            let _ = writeln!(
+                out,
+                "\n{}{}{}{}",
+                pad("最慢模板", 32),
+                align_right("页数", 8),
+                align_right("累计(ms)", 12),
+                align_right("最长(ms)", 12)
+            );
+            for t in &self.templates {
+                let _ = writeln!(
+                    out,
+                    "{}{:>8}{:>12.2}{:>12.2}",
+                    pad(&t.template, 32),
+                    t.pages,
+                    t.total_ms,
+                    t.max_ms
+                );
+            }
+        }
+        if !self.pages.is_empty() {
+            let _ = writeln!(out, "\n{}{}", pad("最慢页面", 56), align_right("耗时(ms)", 10));
+            for p in &self.pages {
+                let _ = writeln!(out, "{}{:>10.2}", pad(&p.url, 56), p.ms);
+            }
+        }
+        out
+    }
+}
+
+/// 终端显示宽度，非 ASCII 字符按双宽计算
+fn display_width(s: &str) -> usize {
+    s.chars().map(|c| if c.is_ascii() { 1 } else { 2 }).sum()
+}
+
+/// 左对齐补空格到指定显示宽度，超出时至少保留一个空格分隔
+fn pad(s: &str, width: usize) -> String {
+    format!("{s}{}", " ".repeat(width.saturating_sub(display_width(s)).max(1)))
+}
+
+/// 右对齐到指定显示宽度
+fn align_right(s: &str, width: usize) -> String {
+    format!("{}{s}", " ".repeat(width.saturating_sub(display_width(s))))
+}
+
+/// 构建过程中的耗时采集器，渲染阶段会在多个线程中同时记录页面耗时
+#[derive(Debug, Default)]
+pub struct Profiler {
+    stages: Mutex<Vec<StageTiming>>,
+    hooks: Mutex<Vec<HookTiming>>,
+    pages: Mutex<Vec<PageTiming>>,
+}
+
+impl Profiler {
+    pub fn record_stage(&self, name: &str, elapsed: Duration) {
+        self.stages
+            .lock()
+            .unwrap()
+            .push(StageTiming::new(name, elapsed));
+    }
+
+    pub fn record_hook(&self, hook: &str, plugin: &str, elapsed: Duration) {
+        self.hooks.lock().unwrap().push(HookTiming {
+            hook: hook.to_owned(),
+            plugin: plugin.to_owned(),
+            ms: millis(elapsed),
+        });
+    }
+
+    pub fn record_page(&self, url: &str, template: &str, elapsed: Duration) {
+        self.pages.lock().unwrap().push(PageTiming {
+            url: url.to_owned(),
+            template: template.to_owned(),
+            ms: millis(elapsed),
+        });
+    }
+
+    /// 汇总为剖析结果：按模板聚合页面耗时，只保留最慢的模板和页面
+    pub fn finish(self) -> BuildProfile {
+        let mut pages = self.pages.into_inner().unwrap();
+
+        let mut by_template: HashMap<String, TemplateTiming> = HashMap::new();
+        for p in &pages {
+            let entry = by_template
+                .entry(p.template.clone())
+                .or_insert_with(|| TemplateTiming {
+                    template: p.template.clone(),
+                    pages: 0,
+                    total_ms: 0.0,
+                    max_ms: 0.0,
+                });
+            entry.pages += 1;
+            entry.total_ms += p.ms;
+            entry.max_ms = entry.max_ms.max(p.ms);
+        }
+        let mut templates: Vec<TemplateTiming> = by_template.into_values().collect();
+        templates.sort_by(|a, b| b.total_ms.total_cmp(&a.total_ms));
+        templates.truncate(SLOWEST_LIMIT);
+
+        pages.sort_by(|a, b| b.ms.total_cmp(&a.ms));
+        pages.truncate(SLOWEST_LIMIT);
+
+        BuildProfile {
+            stages: self.stages.into_inner().unwrap(),
+            hooks: self.hooks.into_inner().unwrap(),
+            templates,
+            pages,
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use crate::build::manifest;
+use crate::config::SiteConfig;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// 历史输出版本的时间戳格式（UTC 毫秒精度，同时作为目录名和版本 ID）
+const RELEASE_ID_FORMAT: &str = "%Y-%m-%dT%H-%M-%S-%3fZ";
+/// 解析版本 ID 时只取到秒的前缀
+const RELEASE_ID_SECONDS: &str = "%Y-%m-%dT%H-%M-%S";
+
+/// 一个可回滚的历史输出版本
+#[derive(Debug, Clone)]
+pub struct Release {
+    pub id: String,
+    /// 版本被替换下线的时间（RFC 3339）
+    pub created_at: String,
+}
+
+fn cache_dir(project_root: &Path, config: &SiteConfig) -> PathBuf {
+    project_root.join(&config.build.cache_dir)
+}
+
+/// 与输出目录同级的隐藏目录 `.{输出目录名}.{suffix}`
+///
+/// 输出目录可以位于项目之外（`--output`），只有放在它旁边才能保证与其处于同一文件系统，
+/// 切换时可以直接 rename
+fn sibling_dir(project_root: &Path, config: &SiteConfig, suffix: &str) -> PathBuf {
+    let output_dir = project_root.join(&config.build.output_dir);
+    let name = output_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "output".into());
+    output_dir.with_file_name(format!(".{name}.{suffix}"))
+}
+
+fn releases_dir(project_root: &Path, config: &SiteConfig) -> PathBuf {
+    sibling_dir(project_root, config, "releases")
+}
+
+/// 构建暂存目录，与输出目录同级
+pub fn staging_dir(project_root: &Path, config: &SiteConfig) -> PathBuf {
+    sibling_dir(project_root, config, "staging")
+}
+
+/// 准备暂存目录：清除上次失败遗留的暂存内容，并以当前输出作为增量构建的基础
+///
+/// 当前输出以硬链接带入暂存目录，增量构建无需复制整个站点；
+/// 构建阶段写入输出时须通过 [`write_file`]、[`copy_file`] 替换文件而非原地修改。
+/// `empty` 为 true（clean 构建）时从空目录开始，不带入当前输出
+pub fn prepare_staging(project_root: &Path, config: &SiteConfig, empty: bool) -> Result<PathBuf> {
+    let staging = staging_dir(project_root, config);
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging)
+            .with_context(|| format!("清除暂存目录失败：{}", staging.display()))?;
+    }
+
+    let output_dir = project_root.join(&config.build.output_dir);
+    if !empty && output_dir.exists() {
+        link_tree(&output_dir, &staging)
+            .with_context(|| format!("链接输出目录到暂存目录失败：{}", staging.display()))?;
+    } else {
+        std::fs::create_dir_all(&staging)?;
+    }
+    Ok(staging)
+}
+
+/// 写入输出文件：先删除已有文件再写入新文件
+///
+/// 暂存目录中未变更的文件与线上输出共享硬链接，原地写入会同时改写线上文件
+pub fn write_file(path: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    remove_existing(path)?;
+    std::fs::write(path, contents)
+}
+
+/// 复制文件到输出，目标已存在时先删除，原因同 [`write_file`]
+pub fn copy_file(src: &Path, dest: &Path) -> std::io::Result<u64> {
+    remove_existing(dest)?;
+    std::fs::copy(src, dest)
+}
+
+/// 文件与其他路径共享硬链接时替换为独立副本，供需要原地追加或覆盖的场景（如插件文件 API）使用
+pub fn detach_file(path: &Path) -> std::io::Result<()> {
+    let Ok(meta) = std::fs::symlink_metadata(path) else {
+        return Ok(());
+    };
+    if !meta.is_file() {
+        return Ok(());
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if meta.nlink() <= 1 {
+            return Ok(());
+        }
+    }
+    let contents = std::fs::read(path)?;
+    write_file(path, contents)?;
+    std::fs::set_permissions(path, meta.permissions())
+}
+
+fn remove_existing(path: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// 构建成功后切换输出：暂存目录替换输出目录，原输出归档为历史版本
+///
+/// `previous_manifest` 为当前输出对应的输出清单内容，随历史版本一起保存，回滚时恢复
+pub fn publish(
+    project_root: &Path,
+    config: &SiteConfig,
+    staging: &Path,
+    previous_manifest: Option<Vec<u8>>,
+) -> Result<()> {
+    let output_dir = project_root.join(&config.build.output_dir);
+
+    // 开发服务器频繁重建，不归档历史版本，也不清理已有的历史版本
+    let keep_history = config.build.keep_releases > 0 && !config.build.live_reload;
+
+    if keep_history && output_dir.exists() {
+        replace_and_archive(project_root, config, staging, previous_manifest.as_deref())?;
+    } else {
+        let trash = sibling_dir(project_root, config, "trash");
+        if trash.exists() {
+            std::fs::remove_dir_all(&trash)?;
+        }
+        swap_output(staging, &output_dir, &trash)?;
+        if trash.exists() {
+            std::fs::remove_dir_all(&trash)?;
+        }
+    }
+
+    if !config.build.live_reload {
+        prune_releases(project_root, config);
+    }
+    Ok(())
+}
+
+/// 列出可回滚的历史版本，最新的在前
+pub fn list(project_root: &Path, config: &SiteConfig) -> Vec<Release> {
+    let Ok(entries) = std::fs::read_dir(releases_dir(project_root, config)) else {
+        return Vec::new();
+    };
+    let mut releases: Vec<Release> = entries
+        .flatten()
+        .filter(|e| e.path().join("output").is_dir())
+        .filter_map(|e| {
+            let id = e.file_name().to_str()?.to_owned();
+            let created_at = chrono::NaiveDateTime::parse_from_str(id.get(..19)?, RELEASE_ID_SECONDS)
+                .ok()?
+                .and_utc()
+                .to_rfc3339();
+            Some(Release { id, created_at })
+        })
+        .collect();
+    releases.sort_by(|a, b| b.id.cmp(&a.id));
+    releases
+}
+
+/// 回滚到指定历史版本：当前输出归档为新的历史版本，目标版本恢复为输出目录
+///
+/// 回滚后输出与内容哈希缓存不再对应，清除哈希缓存和依赖图使下次构建全量重建
+pub fn rollback(project_root: &Path, config: &SiteConfig, id: &str) -> Result<()> {
+    let release_dir = releases_dir(project_root, config).join(id);
+    let release_output = release_dir.join("output");
+    if id.contains(['/', '\\']) || id.starts_with('.') || !release_output.is_dir() {
+        anyhow::bail!("历史版本不存在：{id}");
+    }
+
+    let cache = cache_dir(project_root, config);
+    let manifest_path = cache.join(manifest::FILE_NAME);
+    let current_manifest = std::fs::read(&manifest_path).ok();
+    let restored_manifest = std::fs::read(release_dir.join(manifest::FILE_NAME)).ok();
+
+    let output_dir = project_root.join(&config.build.output_dir);
+    if output_dir.exists() {
+        replace_and_archive(project_root, config, &release_output, current_manifest.as_deref())
+    } else {
+        swap_output(&release_output, &output_dir, &release_dir.join("displaced"))
+    }
+    .with_context(|| format!("恢复历史版本失败：{id}"))?;
+    std::fs::remove_dir_all(&release_dir)?;
+
+    match restored_manifest {
+        Some(data) => std::fs::write(&manifest_path, data)?,
+        None => {
+            let _ = std::fs::remove_file(&manifest_path);
+        }
+    }
+    let _ = std::fs::remove_file(cache.join("hashes.json"));
+    let _ = std::fs::remove_file(cache.join("deps.json"));
+
+    prune_releases(project_root, config);
+    tracing::info!("已回滚到历史版本 {id}");
+    Ok(())
+}
+
+/// 用 `incoming` 替换输出目录，当前输出连同其输出清单归档到 releases/{id}/
+fn replace_and_archive(
+    project_root: &Path,
+    config: &SiteConfig,
+    incoming: &Path,
+    manifest_data: Option<&[u8]>,
+) -> Result<()> {
+    let id = chrono::Utc::now().format(RELEASE_ID_FORMAT).to_string();
+    let release_dir = releases_dir(project_root, config).join(&id);
+    std::fs::create_dir_all(&release_dir)?;
+    if let Some(data) = manifest_data {
+        std::fs::write(release_dir.join(manifest::FILE_NAME), data)?;
+    }
+    let output_dir = project_root.join(&config.build.output_dir);
+    if let Err(e) = swap_output(incoming, &output_dir, &release_dir.join("output")) {
+        let _ = std::fs::remove_dir_all(&release_dir);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// 用 `incoming` 替换输出目录，原输出移到 `displaced`
+///
+/// 优先原子交换两个目录，切换过程中输出目录始终存在；文件系统不支持交换时退回两次 rename，
+/// 第二次失败则把原输出移回，保证线上输出不会丢失
+fn swap_output(incoming: &Path, output_dir: &Path, displaced: &Path) -> Result<()> {
+    if !output_dir.exists() {
+        if let Some(parent) = output_dir.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::rename(incoming, output_dir)
+            .with_context(|| format!("切换输出目录失败：{}", output_dir.display()))?;
+        return Ok(());
+    }
+
+    match exchange(incoming, output_dir) {
+        Ok(()) => {
+            // 交换后 incoming 路径上是原输出；移出失败时留在原处，由下次准备暂存目录时清除
+            if let Err(e) = std::fs::rename(incoming, displaced) {
+                tracing::warn!("移出原输出目录失败 {}: {}", incoming.display(), e);
+            }
+            return Ok(());
+        }
+        Err(e) => tracing::debug!("无法原子交换输出目录，改用 rename：{e}"),
+    }
+
+    std::fs::rename(output_dir, displaced)
+        .with_context(|| format!("移出原输出目录失败：{}", output_dir.display()))?;
+    if let Err(e) = std::fs::rename(incoming, output_dir) {
+        if let Err(restore) = std::fs::rename(displaced, output_dir) {
+            tracing::error!("恢复原输出目录失败 {}: {}", displaced.display(), restore);
+        }
+        return Err(e).with_context(|| format!("切换输出目录失败：{}", output_dir.display()));
+    }
+    Ok(())
+}
+
+/// 原子交换两个路径（Linux renameat2 的 RENAME_EXCHANGE）
+#[cfg(target_os = "linux")]
+fn exchange(a: &Path, b: &Path) -> std::io::Result<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let a = CString::new(a.as_os_str().as_bytes())?;
+    let b = CString::new(b.as_os_str().as_bytes())?;
+    // SAFETY: 两个路径均为以 NUL 结尾的有效 C 字符串，调用期间保持存活
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_renameat2,
+            libc::AT_FDCWD,
+            a.as_ptr(),
+            libc::AT_FDCWD,
+            b.as_ptr(),
+            libc::RENAME_EXCHANGE,
+        )
+    };
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn exchange(_a: &Path, _b: &Path) -> std::io::Result<()> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+/// 只保留最近 keep_releases 个历史版本
+fn prune_releases(project_root: &Path, config: &SiteConfig) {
+    let releases_dir = releases_dir(project_root, config);
+    for release in list(project_root, config)
+        .into_iter()
+        .skip(config.build.keep_releases)
+    {
+        let dir = releases_dir.join(&release.id);
+        if let Err(e) = std::fs::remove_dir_all(&dir) {
+            tracing::warn!("清理历史版本失败 {}: {}", dir.display(), e);
+        }
+    }
+}
+
+/// 递归以硬链接重建目录，符号链接按链接本身复制，无法硬链接时（如跨文件系统）退回复制
+fn link_tree(src: &Path, dest: &Path) -> Result<()> {
+    std::fs::create_dir_all(dest)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let path = entry.path();
+        let target = dest.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_symlink() {
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(std::fs::read_link(&path)?, &target)?;
+        } else if file_type.is_dir() {
+            link_tree(&path, &target)?;
+        } else if std::fs::hard_link(&path, &target).is_err() {
+            std::fs::copy(&path, &target)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use crate::cbtml::filters::filter_slugify;
+use crate::config::RouteConfig;
+use crate::content::Post;
+use chrono::Datelike;
+use std::collections::HashMap;
+
+/// 文章缺少分类时 `{category}` 占位符的取值
+const UNCATEGORIZED: &str = "uncategorized";
+
+/// URL 解析器：按 [routes] 配置的模式展开文章、标签、分类、归档页和独立页面的永久链接
+///
+/// 文章模式支持 `{slug}` `{year}` `{month}` `{day}` `{id}` `{category}`，
+/// 标签/分类/独立页面模式支持 `{slug}`，归档模式支持 `{year}` `{month}`，
+/// 年度归档模式支持 `{year}`；标签云、分类总览和完整归档为固定 URL。
+/// 标签/分类名到 slug 的映射优先取表中存储的 slug，未登记的名称回退到 slugify。
+#[derive(Debug, Clone)]
+pub struct UrlResolver {
+    post_url: String,
+    tag_url: String,
+    category_url: String,
+    archive_url: String,
+    page_url: String,
+    tag_index_url: String,
+    category_index_url: String,
+    archive_index_url: String,
+    archive_year_url: String,
+    tag_slugs: HashMap<String, String>,
+    category_slugs: HashMap<String, String>,
+}
+
+impl UrlResolver {
+    pub fn new(routes: &RouteConfig) -> Self {
+        Self {
+            post_url: routes.post_url.clone(),
+            tag_url: routes.tag_url.clone(),
+            category_url: routes.category_url.clone(),
+            archive_url: routes.archive_url.clone(),
+            page_url: routes.page_url.clone(),
+            tag_index_url: routes.tag_index_url.clone(),
+            category_index_url: routes.category_index_url.clone(),
+            archive_index_url: routes.archive_index_url.clone(),
+            archive_year_url: routes.archive_year_url.clone(),
+            tag_slugs: HashMap::new(),
+            category_slugs: HashMap::new(),
+        }
+    }
+
+    /// 登记标签表中的 (名称, slug)，之后按名称解析标签 URL 时使用存储的 slug
+    pub fn with_tag_slugs<'a>(
+        mut self,
+        tags: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Self {
+        self.tag_slugs.extend(
+            tags.into_iter()
+                .map(|(name, slug)| (name.to_owned(), slug.to_owned())),
+        );
+        self
+    }
+
+    /// 登记分类表中的 (名称, slug)，之后按名称解析分类 URL 时使用存储的 slug
+    pub fn with_category_slugs<'a>(
+        mut self,
+        categories: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Self {
+        self.category_slugs.extend(
+            categories
+                .into_iter()
+                .map(|(name, slug)| (name.to_owned(), slug.to_owned())),
+        );
+        self
+    }
+
+    /// 标签名对应的 slug
+    pub fn tag_slug(&self, name: &str) -> String {
+        match self.tag_slugs.get(name) {
+            Some(slug) => slug.clone(),
+            None => filter_slugify(name.to_owned()),
+        }
+    }
+
+    /// 分类名对应的 slug
+    pub fn category_slug(&self, name: &str) -> String {
+        match self.category_slugs.get(name) {
+            Some(slug) => slug.clone(),
+            None => filter_slugify(name.to_owned()),
+        }
+    }
+
+    /// 文章永久链接
+    pub fn post(&self, post: &Post) -> String {
+        let category = post
+            .category
+            .as_ref()
+            .map(|c| c.slug.clone())
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| UNCATEGORIZED.to_string());
+
+        expand(
+            &self.post_url,
+            &[
+                ("slug", &post.slug),
+                ("year", &format!("{:04}", post.created_at.year())),
+                ("month", &format!("{:02}", post.created_at.month())),
+                ("day", &format!("{:02}", post.created_at.day())),
+                ("id", &post.id.to_string()),
+                ("category", &category),
+            ],
+        )
+    }
+
+    /// 标签归档页 URL（参数为已 slug 化的标签）
+    pub fn tag(&self, slug: &str) -> String {
+        expand(&self.tag_url, &[("slug", slug)])
+    }
+
+    /// 分类归档页 URL（参数为已 slug 化的分类）
+    pub fn category(&self, slug: &str) -> String {
+        expand(&self.category_url, &[("slug", slug)])
+    }
+
+    /// 独立页面 URL
+    pub fn page(&self, slug: &str) -> String {
+        expand(&self.page_url, &[("slug", slug)])
+    }
+
+    /// 月份归档页 URL
+    pub fn archive(&self, year: i32, month: u32) -> String {
+        expand(
+            &self.archive_url,
+            &[
+                ("year", &format!("{:04}", year)),
+                ("month", &format!("{:02}", month)),
+            ],
+        )
+    }
+
+    /// 年度归档页 URL
+    pub fn archive_year(&self, year: i32) -> String {
+        expand(&self.archive_year_url, &[("year", &format!("{:04}", year))])
+    }
+
+    /// 标签云（全部标签）页 URL
+    pub fn tag_index(&self) -> String {
+        self.tag_index_url.clone()
+    }
+
+    /// 分类总览页 URL
+    pub fn category_index(&self) -> String {
+        self.category_index_url.clone()
+    }
+
+    /// 完整归档时间线页 URL
+    pub fn archive_index(&self) -> String {
+        self.archive_index_url.clone()
+    }
+}
+
+/// 将模式中的 `{key}` 占位符替换为对应取值，未知占位符原样保留
+fn expand(pattern: &str, vars: &[(&str, &str)]) -> String {
+    let mut url = pattern.to_owned();
+    for (key, value) in vars {
+        url = url.replace(&format!("{{{key}}}"), value);
+    }
+    url
+}
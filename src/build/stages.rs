@@ -1,7 +1,10 @@
 pub mod assets;
+pub mod feed;
 pub mod finalize;
 pub mod generate;
+pub mod images;
 pub mod load;
+pub mod media;
 pub mod postprocess;
 pub mod render;
 pub mod taxonomy;
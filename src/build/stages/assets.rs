@@ -1,46 +1,241 @@
+use crate::build::incremental::HashCache;
+use crate::build::minify::Savings;
+use crate::build::release::{copy_file, write_file};
 use crate::config::SiteConfig;
-use crate::theme::config::{build_scss_overrides, effective_values, resolve_theme};
+use crate::theme::config::{build_scss_overrides, effective_values, resolve_theme, ResolvedTheme};
 use anyhow::{Context, Result};
-use std::collections::HashMap;
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 
+/// 资源清单文件名，写入输出目录的 assets/ 下
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// 文件名中内容哈希的长度（十六进制字符数）
+const FINGERPRINT_LEN: usize = 8;
+
+/// 资源逻辑名（相对 assets/，如 `main.css`）到实际输出文件名的映射
+///
+/// 未开启指纹或清单中没有的资源按逻辑名原样输出
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AssetManifest(BTreeMap<String, String>);
+
+impl AssetManifest {
+    /// 读取输出目录中的资源清单，不存在时返回空清单
+    pub fn load(output_dir: &Path) -> Self {
+        std::fs::read_to_string(output_dir.join("assets").join(MANIFEST_FILE))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    /// 资源的站点绝对路径
+    pub fn url(&self, name: &str) -> String {
+        let name = name.trim_start_matches('/');
+        let name = name.strip_prefix("assets/").unwrap_or(name);
+        let file = self.0.get(name).map_or(name, String::as_str);
+        format!("/assets/{file}")
+    }
+}
+
+/// 主题 assets/ 下不复制到输出目录的子目录：scss 源码单独编译，admin 为后台内置资源
+const EXCLUDED_DIRS: [&str; 2] = ["scss", "admin"];
+
+/// 沿用旧布局的子目录：其中直接存放的同扩展名文件平铺输出到 assets/ 根目录（`js/main.js` → `/assets/main.js`），
+/// 其余文件与子目录保留相对路径（`js/modules/a.js` → `/assets/js/modules/a.js`）
+const FLAT_DIRS: [&str; 2] = ["css", "js"];
+
+/// 编译/复制主题资源，返回写入输出目录的文件列表与资源清单
 pub fn process_assets(
     project_root: &Path,
+    output_dir: &Path,
     config: &SiteConfig,
     theme_saved_config: &HashMap<String, serde_json::Value>,
-) -> Result<()> {
-    let active = &config.theme.active;
-    let theme_dir = project_root.join("themes").join(active);
-    let output_dir = project_root.join(&config.build.output_dir);
+    minify: &Savings,
+) -> Result<(Vec<PathBuf>, AssetManifest)> {
+    let resolved = resolve_theme(project_root, &config.theme.active)?;
     let assets_out = output_dir.join("assets");
 
     std::fs::create_dir_all(&assets_out)?;
 
-    compile_scss(project_root, &theme_dir, &assets_out, active, theme_saved_config)?;
-    copy_css(&theme_dir, &assets_out)?;
-    copy_js(&theme_dir, &assets_out)?;
-    copy_media(project_root, &output_dir)?;
+    let mut written = Vec::new();
+    written.extend(compile_scss(
+        project_root,
+        &resolved,
+        &assets_out,
+        theme_saved_config,
+    )?);
+    written.extend(copy_theme_assets(project_root, &resolved.parent_chain, &assets_out)?);
+    minify_assets(config, &written, minify)?;
+
+    let manifest = if config.build.fingerprint_assets {
+        let manifest = fingerprint(&assets_out, &mut written)?;
+        let manifest_path = assets_out.join(MANIFEST_FILE);
+        write_file(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+        written.push(manifest_path);
+        manifest
+    } else {
+        AssetManifest::default()
+    };
+
+    Ok((written, manifest))
+}
+
+/// 收集继承链上所有主题的静态资源，返回相对 assets/ 的路径到源文件的映射
+///
+/// 从根主题向当前主题依次合并，子主题的同路径文件覆盖父主题；
+/// css/ 与 js/ 下直接存放的样式、脚本按旧布局平铺，其余文件保留相对路径
+pub fn collect_theme_assets(project_root: &Path, parent_chain: &[String]) -> BTreeMap<String, PathBuf> {
+    fn walk(base: &Path, dir: &Path, files: &mut BTreeMap<String, PathBuf>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with('.') {
+                continue;
+            }
+            if path.is_dir() {
+                if dir == base && EXCLUDED_DIRS.contains(&name.as_ref()) {
+                    continue;
+                }
+                if dir == base && FLAT_DIRS.contains(&name.as_ref()) {
+                    collect_flat(base, &path, &name, files);
+                    continue;
+                }
+                walk(base, &path, files);
+            } else if let Ok(rel) = path.strip_prefix(base) {
+                files.insert(rel.to_string_lossy().replace('\\', "/"), path);
+            }
+        }
+    }
+
+    fn collect_flat(base: &Path, dir: &Path, ext: &str, files: &mut BTreeMap<String, PathBuf>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with('.') {
+                continue;
+            }
+            if path.is_dir() {
+                walk(base, &path, files);
+            } else if path.extension().is_some_and(|e| e == ext) {
+                files.insert(name.into_owned(), path);
+            } else if let Ok(rel) = path.strip_prefix(base) {
+                files.insert(rel.to_string_lossy().replace('\\', "/"), path);
+            }
+        }
+    }
+
+    let themes_dir = project_root.join("themes");
+    let mut files = BTreeMap::new();
+    for theme in parent_chain.iter().rev() {
+        let assets_dir = themes_dir.join(theme).join("assets");
+        walk(&assets_dir, &assets_dir, &mut files);
+    }
+    files
+}
+
+fn copy_theme_assets(project_root: &Path, parent_chain: &[String], assets_out: &Path) -> Result<Vec<PathBuf>> {
+    let mut copied = Vec::new();
+    for (rel, src) in collect_theme_assets(project_root, parent_chain) {
+        let dest = assets_out.join(&rel);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        copy_file(&src, &dest)
+            .with_context(|| format!("复制资源失败: {}", src.display()))?;
+        tracing::debug!("已复制资源: {}", src.display());
+        copied.push(dest);
+    }
+    Ok(copied)
+}
+
+/// 为 CSS/JS 文件生成带内容哈希的副本（`main.css` → `main.3f2a9c1b.css`），
+/// 副本追加到写入列表，返回对应的资源清单
+///
+/// 原文件保留：模块间的相对 `import`、CSS 的 `@import` 仍按原文件名引用
+fn fingerprint(assets_out: &Path, written: &mut Vec<PathBuf>) -> Result<AssetManifest> {
+    let mut manifest = AssetManifest::default();
+    let mut hashed = Vec::new();
+    for path in written.iter() {
+        let (Some(stem), Some(ext)) = (
+            path.file_stem().and_then(|s| s.to_str()),
+            path.extension().and_then(|e| e.to_str()),
+        ) else {
+            continue;
+        };
+        if ext != "css" && ext != "js" {
+            continue;
+        }
+        let Ok(logical) = path.strip_prefix(assets_out) else {
+            continue;
+        };
+        let logical = logical.to_string_lossy().replace('\\', "/");
 
+        let hash = HashCache::compute_hash(path)?;
+        let hashed_name = format!("{stem}.{}.{ext}", &hash[..FINGERPRINT_LEN]);
+        let hashed_path = path.with_file_name(&hashed_name);
+        copy_file(path, &hashed_path)
+            .with_context(|| format!("复制指纹资源失败: {}", path.display()))?;
+
+        let hashed_logical = match logical.rsplit_once('/') {
+            Some((dir, _)) => format!("{dir}/{hashed_name}"),
+            None => hashed_name,
+        };
+        manifest.0.insert(logical, hashed_logical);
+        hashed.push(hashed_path);
+    }
+    written.extend(hashed);
+    Ok(manifest)
+}
+
+/// 按 [build.minify] 配置就地压缩本次写入的 CSS 与 JS 文件
+fn minify_assets(config: &SiteConfig, files: &[PathBuf], minify: &Savings) -> Result<()> {
+    let options = &config.build.minify;
+    for path in files {
+        // 主题自带的第三方压缩版本（如 vendor.min.js）无需再处理
+        if path.file_stem().is_some_and(|stem| stem.to_string_lossy().ends_with(".min")) {
+            continue;
+        }
+        let ext = path.extension().and_then(|e| e.to_str());
+        let minified = match ext {
+            Some("css") if options.css => minify.css(&std::fs::read_to_string(path)?),
+            Some("js") if options.js => minify.js(&std::fs::read_to_string(path)?),
+            _ => continue,
+        };
+        write_file(path, minified)
+            .with_context(|| format!("写入压缩结果失败: {}", path.display()))?;
+    }
     Ok(())
 }
 
+/// 编译继承链上最近一个提供 main.scss 的主题的样式
 fn compile_scss(
     project_root: &Path,
-    theme_dir: &Path,
+    resolved: &ResolvedTheme,
     assets_out: &Path,
-    theme_name: &str,
     theme_saved_config: &HashMap<String, serde_json::Value>,
-) -> Result<()> {
-    let scss_dir = theme_dir.join("assets").join("scss");
-    let main_scss = scss_dir.join("main.scss");
-
-    if !main_scss.exists() {
+) -> Result<Option<PathBuf>> {
+    let themes_dir = project_root.join("themes");
+    let Some(scss_dir) = resolved
+        .parent_chain
+        .iter()
+        .map(|theme| themes_dir.join(theme).join("assets").join("scss"))
+        .find(|dir| dir.join("main.scss").exists())
+    else {
         tracing::debug!("主题无 main.scss，跳过 SCSS 编译");
-        return Ok(());
-    }
+        return Ok(None);
+    };
+    let main_scss = scss_dir.join("main.scss");
 
     // 从主题配置 schema 提取默认值，生成 SCSS 变量覆盖
-    let resolved = resolve_theme(project_root, theme_name)?;
     let values = effective_values(&resolved.config_schema, theme_saved_config);
     let overrides = build_scss_overrides(&values);
 
@@ -64,59 +259,9 @@ fn compile_scss(
     let css = grass::from_string(input, &options)
         .map_err(|e| anyhow::anyhow!("SCSS 编译失败: {e}"))?;
 
-    std::fs::write(assets_out.join("main.css"), css)?;
+    let css_path = assets_out.join("main.css");
+    write_file(&css_path, css)?;
     tracing::info!("已编译 main.scss → main.css");
 
-    Ok(())
-}
-
-fn copy_css(theme_dir: &Path, assets_out: &Path) -> Result<()> {
-    let css_dir = theme_dir.join("assets").join("css");
-    if !css_dir.exists() {
-        return Ok(());
-    }
-    copy_files_with_ext(&css_dir, assets_out, "css")
-}
-
-fn copy_js(theme_dir: &Path, assets_out: &Path) -> Result<()> {
-    let js_dir = theme_dir.join("assets").join("js");
-    if !js_dir.exists() {
-        return Ok(());
-    }
-    copy_files_with_ext(&js_dir, assets_out, "js")
-}
-
-fn copy_files_with_ext(src_dir: &Path, dest_dir: &Path, ext: &str) -> Result<()> {
-    for entry in std::fs::read_dir(src_dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.is_file() && path.extension().is_some_and(|e| e == ext)
-            && let Some(name) = path.file_name() {
-                std::fs::copy(&path, dest_dir.join(name))?;
-                tracing::debug!("已复制资源: {}", path.display());
-            }
-    }
-    Ok(())
-}
-
-fn copy_media(project_root: &Path, output_dir: &Path) -> Result<()> {
-    let media_src = project_root.join("media");
-    if !media_src.exists() {
-        return Ok(());
-    }
-    let media_dest = output_dir.join("media");
-
-    if media_dest.exists() || media_dest.is_symlink() {
-        if media_dest.is_symlink() {
-            std::fs::remove_file(&media_dest)?;
-        } else {
-            std::fs::remove_dir_all(&media_dest)?;
-        }
-    }
-
-    #[cfg(unix)]
-    std::os::unix::fs::symlink(&media_src, &media_dest)?;
-
-    tracing::info!("已创建 media 符号链接");
-    Ok(())
+    Ok(Some(css_path))
 }
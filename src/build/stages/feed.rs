@@ -0,0 +1,505 @@
+use crate::build::release::write_file;
+use crate::build::stages::finalize::{absolute_url, xml_escape, FinalizeContext};
+use crate::config::SiteConfig;
+use crate::content::{Post, PostRef};
+use crate::media::process::format_to_mime;
+use anyhow::Result;
+use image::ImageFormat;
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// 支持的订阅格式：配置名、文件名、MIME 类型、显示名称
+const FORMATS: [(&str, &str, &str, &str); 3] = [
+    ("rss", "feed.xml", "application/rss+xml", "RSS"),
+    ("atom", "atom.xml", "application/atom+xml", "Atom"),
+    ("json", "feed.json", "application/feed+json", "JSON Feed"),
+];
+
+/// 正文 HTML 中的链接与资源属性
+static URL_ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)(\s(?:href|src|poster)\s*=\s*)(?:"([^"]*)"|'([^']*)')"#).unwrap()
+});
+
+/// 带协议的 URL（`https:`、`mailto:`、`data:` 等）
+static URL_SCHEME_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[a-zA-Z][a-zA-Z0-9+.-]*:").unwrap());
+
+fn format_info(name: &str) -> Option<(&'static str, &'static str, &'static str)> {
+    FORMATS
+        .iter()
+        .find(|(n, ..)| *n == name)
+        .map(|&(_, file, mime, label)| (file, mime, label))
+}
+
+/// 页面 URL 对应的订阅文件目录：目录形式直接使用，文件形式（如 `/tags/rust.html`）去掉扩展名
+pub fn feed_dir(url: &str) -> String {
+    if url.ends_with('/') {
+        return url.to_owned();
+    }
+    let stem = match url.rsplit_once('.') {
+        Some((stem, _)) if !stem.ends_with('/') => stem,
+        _ => url,
+    };
+    format!("{stem}/")
+}
+
+/// 供主题输出 `<link rel="alternate">` 的订阅地址列表，每项含 type、title、url
+pub fn feed_links(config: &SiteConfig, dir: &str, title: &str) -> Vec<serde_json::Value> {
+    if !config.feed.enabled {
+        return Vec::new();
+    }
+    config
+        .feed
+        .format
+        .iter()
+        .filter_map(|name| format_info(name))
+        .map(|(file, mime, label)| {
+            serde_json::json!({
+                "type": mime,
+                "title": format!("{title} {label}"),
+                "url": format!("{dir}{file}"),
+            })
+        })
+        .collect()
+}
+
+/// 一个订阅源：全站，或单个标签、分类
+struct Channel<'a> {
+    title: String,
+    description: String,
+    /// 对应的 HTML 页面路径
+    link: String,
+    /// 订阅文件所在目录，以 / 结尾
+    dir: String,
+    posts: Vec<&'a Post>,
+}
+
+/// 文章封面生成的附件
+struct Enclosure {
+    url: String,
+    length: u64,
+    mime: String,
+}
+
+/// 各订阅源共用的条目数据，每篇文章只处理一次
+struct Item {
+    /// 链接已改写为绝对 URL 的正文，仅在 full_content 时生成
+    content: Option<String>,
+    enclosure: Option<Enclosure>,
+}
+
+/// 按 [feed] format 生成全站订阅，以及按需生成的标签、分类订阅，返回写入的文件列表
+pub fn generate_feeds(ctx: &FinalizeContext) -> Result<Vec<PathBuf>> {
+    let (config, taxonomy, resolver) = (ctx.config, ctx.taxonomy, ctx.resolver);
+    for name in &config.feed.format {
+        if format_info(name).is_none() {
+            tracing::warn!("未知的 feed 格式：{}", name);
+        }
+    }
+
+    let limit = config.feed.post_count;
+    let mut channels = vec![Channel {
+        title: config.site.title.clone(),
+        description: config.site.description.clone(),
+        link: "/".into(),
+        dir: "/".into(),
+        posts: ctx.posts.iter().take(limit).collect(),
+    }];
+
+    let by_url: HashMap<&str, &Post> = ctx.posts.iter().map(|p| (p.url.as_str(), p)).collect();
+    let term_posts = |refs: &[PostRef]| -> Vec<&Post> {
+        refs.iter()
+            .filter_map(|r| by_url.get(r.url.as_str()).copied())
+            .take(limit)
+            .collect()
+    };
+
+    if config.feed.per_tag {
+        for (tag, refs) in &taxonomy.tags {
+            let link = resolver.tag(&resolver.tag_slug(tag));
+            let description = taxonomy
+                .tag_info
+                .get(tag)
+                .map(|t| t.description.clone())
+                .filter(|d| !d.is_empty())
+                .unwrap_or_else(|| format!("包含标签「{}」的所有文章", tag));
+            channels.push(Channel {
+                title: format!("{} - 标签：{}", config.site.title, tag),
+                description,
+                dir: feed_dir(&link),
+                link,
+                posts: term_posts(refs),
+            });
+        }
+    }
+
+    if config.feed.per_category {
+        for (cat, refs) in &taxonomy.categories {
+            let link = resolver.category(&resolver.category_slug(cat));
+            let description = taxonomy
+                .category_info
+                .get(cat)
+                .map(|i| i.description.clone())
+                .filter(|d| !d.is_empty())
+                .unwrap_or_else(|| format!("分类「{}」下的所有文章", cat));
+            channels.push(Channel {
+                title: format!("{} - 分类：{}", config.site.title, cat),
+                description,
+                dir: feed_dir(&link),
+                link,
+                posts: term_posts(refs),
+            });
+        }
+    }
+
+    let media_dir = ctx.project_root.join(&config.media.upload_dir);
+    let mut items: HashMap<&str, Item> = HashMap::new();
+    for post in channels.iter().flat_map(|c| &c.posts) {
+        items.entry(post.url.as_str()).or_insert_with(|| Item {
+            content: config
+                .feed
+                .full_content
+                .then(|| absolutize_html(post.content.html(), ctx.site_url, &post.url)),
+            enclosure: post
+                .cover_image
+                .as_deref()
+                .and_then(|src| enclosure(ctx.site_url, &media_dir, src)),
+        });
+    }
+
+    let writer = FeedWriter {
+        config,
+        site_url: ctx.site_url,
+        items: &items,
+    };
+    let mut written = Vec::new();
+    for channel in &channels {
+        let dir = ctx.output_dir.join(channel.dir.trim_start_matches('/'));
+        for name in &config.feed.format {
+            let Some((file, ..)) = format_info(name) else {
+                continue;
+            };
+            let content = match name.as_str() {
+                "rss" => writer.rss(channel),
+                "atom" => writer.atom(channel),
+                _ => writer.json_feed(channel)?,
+            };
+            std::fs::create_dir_all(&dir)?;
+            let path = dir.join(file);
+            write_file(&path, content)?;
+            written.push(path);
+        }
+    }
+
+    tracing::info!("已生成 {} 个订阅源（{} 个文件）", channels.len(), written.len());
+    Ok(written)
+}
+
+/// 封面图对应的附件；`/media/` 下的文件读取实际大小，站外图片大小记为 0
+fn enclosure(site_url: &str, media_dir: &Path, src: &str) -> Option<Enclosure> {
+    let url = absolute_url(site_url, src)?;
+    let path = src.split(['?', '#']).next().unwrap_or(src);
+    let length = path
+        .strip_prefix("/media/")
+        .filter(|rel| rel.split('/').all(|s| !s.is_empty() && s != "." && s != ".."))
+        .and_then(|rel| std::fs::metadata(media_dir.join(rel)).ok())
+        .map_or(0, |m| m.len());
+    Some(Enclosure {
+        url,
+        length,
+        mime: format_to_mime(ImageFormat::from_path(path).ok()),
+    })
+}
+
+/// 将正文中相对的 href、src 改写为绝对 URL，阅读器中没有站点上下文可供解析
+fn absolutize_html(html: &str, site_url: &str, page_url: &str) -> String {
+    URL_ATTR_RE
+        .replace_all(html, |caps: &Captures| {
+            let (quote, value) = match caps.get(2) {
+                Some(m) => ('"', m.as_str()),
+                None => ('\'', caps.get(3).map_or("", |m| m.as_str())),
+            };
+            format!(
+                "{}{quote}{}{quote}",
+                &caps[1],
+                resolve_url(site_url, page_url, value)
+            )
+        })
+        .into_owned()
+}
+
+/// 按页面地址解析 URL：站内绝对路径补全站点地址，相对路径相对于页面所在目录，
+/// 已带协议的 URL 原样保留
+fn resolve_url(site_url: &str, page_url: &str, url: &str) -> String {
+    if url.is_empty() || URL_SCHEME_RE.is_match(url) {
+        return url.to_owned();
+    }
+    if url.starts_with("//") {
+        let scheme = site_url.split_once("://").map_or("https", |(s, _)| s);
+        return format!("{scheme}:{url}");
+    }
+    if url.starts_with('/') {
+        return format!("{site_url}{url}");
+    }
+    if url.starts_with('#') || url.starts_with('?') {
+        return format!("{site_url}{page_url}{url}");
+    }
+
+    let (path, suffix) = url.split_at(url.find(['?', '#']).unwrap_or(url.len()));
+    let dir = &page_url[..page_url.rfind('/').map_or(0, |i| i + 1)];
+    let mut segments: Vec<&str> = dir.split('/').filter(|s| !s.is_empty()).collect();
+    let parts: Vec<&str> = path.split('/').collect();
+    for (i, part) in parts.iter().enumerate() {
+        match *part {
+            "." => {}
+            ".." => {
+                segments.pop();
+            }
+            "" if i + 1 < parts.len() => {}
+            other => segments.push(other),
+        }
+    }
+    format!("{site_url}/{}{suffix}", segments.join("/"))
+}
+
+/// CDATA 段中不能出现 `]]>`，拆成两段输出
+fn cdata(s: &str) -> String {
+    format!("<![CDATA[{}]]>", s.replace("]]>", "]]]]><![CDATA[>"))
+}
+
+struct FeedWriter<'a> {
+    config: &'a SiteConfig,
+    site_url: &'a str,
+    items: &'a HashMap<&'a str, Item>,
+}
+
+impl FeedWriter<'_> {
+    fn item(&self, post: &Post) -> Option<&Item> {
+        self.items.get(post.url.as_str())
+    }
+
+    fn rss(&self, channel: &Channel) -> String {
+        let config = self.config;
+        let site_url = xml_escape(self.site_url);
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str("<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\"");
+        if config.feed.full_content {
+            xml.push_str(" xmlns:content=\"http://purl.org/rss/1.0/modules/content/\"");
+        }
+        xml.push_str(">\n");
+        xml.push_str("  <channel>\n");
+        xml.push_str(&format!("    <title>{}</title>\n", xml_escape(&channel.title)));
+        xml.push_str(&format!("    <link>{}{}</link>\n", site_url, xml_escape(&channel.link)));
+        xml.push_str(&format!(
+            "    <description>{}</description>\n",
+            xml_escape(&channel.description)
+        ));
+        xml.push_str(&format!("    <language>{}</language>\n", config.site.language));
+        xml.push_str(&format!(
+            "    <atom:link href=\"{}{}feed.xml\" rel=\"self\" type=\"application/rss+xml\" />\n",
+            site_url,
+            xml_escape(&channel.dir)
+        ));
+
+        if let Some(post) = channel.posts.first() {
+            xml.push_str(&format!(
+                "    <lastBuildDate>{}</lastBuildDate>\n",
+                post.updated_at.to_rfc2822()
+            ));
+        }
+
+        for post in &channel.posts {
+            let item = self.item(post);
+            xml.push_str("    <item>\n");
+            xml.push_str(&format!("      <title>{}</title>\n", xml_escape(&post.title)));
+            xml.push_str(&format!(
+                "      <link>{}{}</link>\n",
+                site_url,
+                xml_escape(&post.url)
+            ));
+            xml.push_str(&format!(
+                "      <guid isPermaLink=\"true\">{}{}</guid>\n",
+                site_url,
+                xml_escape(&post.url)
+            ));
+            xml.push_str(&format!(
+                "      <pubDate>{}</pubDate>\n",
+                post.created_at.to_rfc2822()
+            ));
+            if let Some(excerpt) = &post.excerpt {
+                xml.push_str(&format!(
+                    "      <description>{}</description>\n",
+                    xml_escape(excerpt)
+                ));
+            }
+            if let Some(content) = item.and_then(|i| i.content.as_deref()) {
+                xml.push_str(&format!(
+                    "      <content:encoded>{}</content:encoded>\n",
+                    cdata(content)
+                ));
+            }
+            if let Some(enclosure) = item.and_then(|i| i.enclosure.as_ref()) {
+                xml.push_str(&format!(
+                    "      <enclosure url=\"{}\" length=\"{}\" type=\"{}\" />\n",
+                    xml_escape(&enclosure.url),
+                    enclosure.length,
+                    enclosure.mime
+                ));
+            }
+            for tag in &post.tags {
+                xml.push_str(&format!("      <category>{}</category>\n", xml_escape(&tag.name)));
+            }
+            xml.push_str("    </item>\n");
+        }
+
+        xml.push_str("  </channel>\n</rss>\n");
+        xml
+    }
+
+    fn atom(&self, channel: &Channel) -> String {
+        let config = self.config;
+        let site_url = xml_escape(self.site_url);
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+        xml.push_str(&format!("  <title>{}</title>\n", xml_escape(&channel.title)));
+        xml.push_str(&format!(
+            "  <link href=\"{}{}\" />\n",
+            site_url,
+            xml_escape(&channel.link)
+        ));
+        xml.push_str(&format!(
+            "  <link href=\"{}{}atom.xml\" rel=\"self\" type=\"application/atom+xml\" />\n",
+            site_url,
+            xml_escape(&channel.dir)
+        ));
+        xml.push_str(&format!("  <id>{}{}</id>\n", site_url, xml_escape(&channel.link)));
+
+        if !config.site.author.name.is_empty() {
+            xml.push_str("  <author>\n");
+            xml.push_str(&format!(
+                "    <name>{}</name>\n",
+                xml_escape(&config.site.author.name)
+            ));
+            if !config.site.author.email.is_empty() {
+                xml.push_str(&format!(
+                    "    <email>{}</email>\n",
+                    xml_escape(&config.site.author.email)
+                ));
+            }
+            xml.push_str("  </author>\n");
+        }
+
+        if let Some(post) = channel.posts.first() {
+            xml.push_str(&format!(
+                "  <updated>{}</updated>\n",
+                post.updated_at.to_rfc3339()
+            ));
+        }
+
+        for post in &channel.posts {
+            let item = self.item(post);
+            xml.push_str("  <entry>\n");
+            xml.push_str(&format!("    <title>{}</title>\n", xml_escape(&post.title)));
+            xml.push_str(&format!(
+                "    <link href=\"{}{}\" />\n",
+                site_url,
+                xml_escape(&post.url)
+            ));
+            if let Some(enclosure) = item.and_then(|i| i.enclosure.as_ref()) {
+                xml.push_str(&format!(
+                    "    <link rel=\"enclosure\" href=\"{}\" length=\"{}\" type=\"{}\" />\n",
+                    xml_escape(&enclosure.url),
+                    enclosure.length,
+                    enclosure.mime
+                ));
+            }
+            xml.push_str(&format!(
+                "    <id>{}{}</id>\n",
+                site_url,
+                xml_escape(&post.url)
+            ));
+            xml.push_str(&format!(
+                "    <published>{}</published>\n",
+                post.created_at.to_rfc3339()
+            ));
+            xml.push_str(&format!(
+                "    <updated>{}</updated>\n",
+                post.updated_at.to_rfc3339()
+            ));
+            if let Some(excerpt) = &post.excerpt {
+                xml.push_str(&format!(
+                    "    <summary>{}</summary>\n",
+                    xml_escape(excerpt)
+                ));
+            }
+            if let Some(content) = item.and_then(|i| i.content.as_deref()) {
+                xml.push_str(&format!(
+                    "    <content type=\"html\">{}</content>\n",
+                    xml_escape(content)
+                ));
+            }
+            xml.push_str("  </entry>\n");
+        }
+
+        xml.push_str("</feed>\n");
+        xml
+    }
+
+    /// JSON Feed 1.1（https://jsonfeed.org/version/1.1）
+    fn json_feed(&self, channel: &Channel) -> Result<String> {
+        let config = self.config;
+        let site_url = self.site_url;
+        let items: Vec<serde_json::Value> = channel
+            .posts
+            .iter()
+            .map(|post| {
+                let url = format!("{site_url}{}", post.url);
+                let mut item = serde_json::json!({
+                    "id": url,
+                    "url": url,
+                    "title": post.title,
+                    "date_published": post.created_at.to_rfc3339(),
+                    "date_modified": post.updated_at.to_rfc3339(),
+                });
+                if let Some(excerpt) = &post.excerpt {
+                    item["summary"] = excerpt.as_str().into();
+                }
+                // content_html 与 content_text 至少需要其一
+                match self.item(post).and_then(|i| i.content.as_deref()) {
+                    Some(content) => item["content_html"] = content.into(),
+                    None => {
+                        item["content_text"] = post.excerpt.as_deref().unwrap_or_default().into()
+                    }
+                }
+                if let Some(enclosure) = self.item(post).and_then(|i| i.enclosure.as_ref()) {
+                    item["image"] = enclosure.url.as_str().into();
+                }
+                if !post.tags.is_empty() {
+                    item["tags"] = post.tag_names().into();
+                }
+                item
+            })
+            .collect();
+
+        let mut feed = serde_json::json!({
+            "version": "https://jsonfeed.org/version/1.1",
+            "title": channel.title,
+            "home_page_url": format!("{site_url}{}", channel.link),
+            "feed_url": format!("{site_url}{}feed.json", channel.dir),
+            "description": channel.description,
+            "language": config.site.language,
+            "items": items,
+        });
+        if !config.site.author.name.is_empty() {
+            let mut author = serde_json::json!({ "name": config.site.author.name });
+            if let Some(avatar) = absolute_url(site_url, &config.site.author.avatar) {
+                author["avatar"] = avatar.into();
+            }
+            feed["authors"] = serde_json::json!([author]);
+        }
+
+        Ok(serde_json::to_string_pretty(&feed)?)
+    }
+}
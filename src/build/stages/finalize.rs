@@ -1,202 +1,242 @@
+use crate::build::release::write_file;
+use crate::build::routes::UrlResolver;
+use crate::build::stages::feed;
+use crate::build::stages::generate::{post_dep, RenderPage};
 use crate::config::SiteConfig;
-use crate::content::Post;
+use crate::content::{Page, Post, TaxonomyIndex};
 use anyhow::Result;
-use std::path::Path;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
 
-/// 构建收尾：生成 sitemap.xml、feed.xml、search-index.json 等
-pub fn finalize(project_root: &Path, config: &SiteConfig, posts: &[Post]) -> Result<()> {
-    let output_dir = project_root.join(&config.build.output_dir);
+/// 构建收尾所需的站点数据
+pub struct FinalizeContext<'a> {
+    pub project_root: &'a Path,
+    pub output_dir: &'a Path,
+    pub config: &'a SiteConfig,
+    /// 生效的站点地址（后台设置优先于 cblog.toml），不含末尾的 `/`
+    pub site_url: &'a str,
+    pub posts: &'a [Post],
+    pub pages: &'a [Page],
+    /// 本次生成的全部页面
+    pub rendered: &'a [RenderPage],
+    pub taxonomy: &'a TaxonomyIndex,
+    pub resolver: &'a UrlResolver,
+}
+
+/// 构建收尾：生成 sitemap.xml、feed.xml、search-index.json 等，返回写入的文件列表
+pub fn finalize(ctx: &FinalizeContext) -> Result<Vec<PathBuf>> {
+    let config = ctx.config;
+    let mut written = Vec::new();
 
     if config.sitemap.enabled {
-        generate_sitemap(&output_dir, config, posts)?;
+        written.extend(generate_sitemap(ctx)?);
     }
 
     if config.feed.enabled {
-        generate_feed(&output_dir, config, posts)?;
+        written.extend(feed::generate_feeds(ctx)?);
     }
 
     if config.features.search.enabled {
-        generate_search_index(&output_dir, config, posts)?;
+        generate_search_index(ctx.output_dir, config, ctx.posts)?;
+        written.push(ctx.output_dir.join("search-index.json"));
     }
 
-    Ok(())
+    Ok(written)
 }
 
-fn generate_sitemap(output_dir: &Path, config: &SiteConfig, posts: &[Post]) -> Result<()> {
-    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
-    xml.push_str("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+/// 单个 sitemap 文件最多包含的 URL 数，超出时拆分并生成 sitemap 索引
+const SITEMAP_MAX_URLS: usize = 50_000;
 
-    xml.push_str(&format!(
-        "  <url>\n    <loc>{}/</loc>\n    <changefreq>{}</changefreq>\n    <priority>1.0</priority>\n  </url>\n",
-        xml_escape(&config.site.url), config.sitemap.change_freq
-    ));
+/// 单个 URL 最多列出的图片数
+const SITEMAP_MAX_IMAGES: usize = 1_000;
 
-    for post in posts {
-        xml.push_str(&format!(
-            "  <url>\n    <loc>{}/posts/{}/</loc>\n    <lastmod>{}</lastmod>\n    <changefreq>{}</changefreq>\n    <priority>{}</priority>\n  </url>\n",
-            xml_escape(&config.site.url),
-            post.slug,
-            post.updated_at.format("%Y-%m-%d"),
-            config.sitemap.change_freq,
-            config.sitemap.priority
-        ));
-    }
+/// 正文中 `<img>` 的 src
+static IMG_SRC_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"<img\b[^>]*?\ssrc\s*=\s*["']([^"']+)["']"#).unwrap());
 
-    xml.push_str("</urlset>\n");
-    std::fs::write(output_dir.join("sitemap.xml"), xml)?;
-    tracing::info!("已生成 sitemap.xml");
-    Ok(())
+struct SitemapEntry<'a> {
+    loc: &'a str,
+    lastmod: Option<DateTime<Utc>>,
+    change_freq: &'a str,
+    priority: f32,
+    images: Vec<String>,
 }
 
-fn generate_feed(output_dir: &Path, config: &SiteConfig, posts: &[Post]) -> Result<()> {
-    let count = config.feed.post_count.min(posts.len());
-    let feed_posts = &posts[..count];
+/// 生成 sitemap：收录全部生成的页面（主题特殊页面与 noindex 文章除外），
+/// URL 数超过上限时拆分为 sitemap-N.xml，sitemap.xml 改为索引
+fn generate_sitemap(ctx: &FinalizeContext) -> Result<Vec<PathBuf>> {
+    let (config, site_url, output_dir) = (ctx.config, ctx.site_url, ctx.output_dir);
+    let by_dep: HashMap<String, &Post> = ctx.posts.iter().map(|p| (post_dep(&p.slug), p)).collect();
+    let posts_by_url: HashMap<&str, &Post> = ctx.posts.iter().map(|p| (p.url.as_str(), p)).collect();
+    let pages_by_url: HashMap<&str, &Page> = ctx.pages.iter().map(|p| (p.url.as_str(), p)).collect();
 
-    for format in &config.feed.format {
-        match format.as_str() {
-            "rss" => generate_rss(output_dir, config, feed_posts)?,
-            "atom" => generate_atom(output_dir, config, feed_posts)?,
-            _ => tracing::warn!("未知的 feed 格式：{}", format),
-        }
-    }
+    let mut entries = Vec::new();
+    for page in ctx.rendered {
+        let page_type = page.context["page"]["type"].as_str().unwrap_or_default();
+        let Some((change_freq, priority)) = sitemap_settings(config, page_type) else {
+            continue;
+        };
 
-    Ok(())
-}
+        let (lastmod, images) = match page_type {
+            "post" => {
+                let Some(post) = posts_by_url.get(page.url.as_str()) else {
+                    continue;
+                };
+                if post.noindex {
+                    continue;
+                }
+                let mut images: Vec<String> = post.cover_image.iter().cloned().collect();
+                images.extend(inline_images(post.content.html()));
+                (Some(post.updated_at), images)
+            }
+            "page" => match pages_by_url.get(page.url.as_str()) {
+                Some(p) => (Some(p.updated_at), inline_images(p.content.html())),
+                None => (None, Vec::new()),
+            },
+            // 列表页以当页文章中最近的更新时间作为 lastmod
+            _ => (
+                page.deps.iter().filter_map(|d| by_dep.get(d)).map(|p| p.updated_at).max(),
+                Vec::new(),
+            ),
+        };
 
-fn generate_rss(output_dir: &Path, config: &SiteConfig, posts: &[Post]) -> Result<()> {
-    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
-    xml.push_str("<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\">\n");
-    xml.push_str("  <channel>\n");
-    xml.push_str(&format!("    <title>{}</title>\n", xml_escape(&config.site.title)));
-    xml.push_str(&format!("    <link>{}</link>\n", xml_escape(&config.site.url)));
-    xml.push_str(&format!(
-        "    <description>{}</description>\n",
-        xml_escape(&config.site.description)
-    ));
-    xml.push_str(&format!("    <language>{}</language>\n", config.site.language));
-    xml.push_str(&format!(
-        "    <atom:link href=\"{}/feed.xml\" rel=\"self\" type=\"application/rss+xml\" />\n",
-        xml_escape(&config.site.url)
-    ));
-
-    if let Some(post) = posts.first() {
-        xml.push_str(&format!(
-            "    <lastBuildDate>{}</lastBuildDate>\n",
-            post.updated_at.to_rfc2822()
-        ));
+        let mut seen = HashSet::new();
+        let images = images
+            .iter()
+            .filter_map(|src| absolute_url(site_url, src))
+            .filter(|url| seen.insert(url.clone()))
+            .take(SITEMAP_MAX_IMAGES)
+            .collect();
+
+        entries.push(SitemapEntry {
+            loc: &page.url,
+            lastmod,
+            change_freq,
+            priority,
+            images,
+        });
     }
 
-    for post in posts {
-        xml.push_str("    <item>\n");
-        xml.push_str(&format!("      <title>{}</title>\n", xml_escape(&post.title)));
-        xml.push_str(&format!(
-            "      <link>{}/posts/{}/</link>\n",
-            xml_escape(&config.site.url),
-            post.slug
-        ));
-        xml.push_str(&format!(
-            "      <guid isPermaLink=\"true\">{}/posts/{}/</guid>\n",
-            xml_escape(&config.site.url),
-            post.slug
-        ));
-        xml.push_str(&format!(
-            "      <pubDate>{}</pubDate>\n",
-            post.created_at.to_rfc2822()
-        ));
-        if let Some(excerpt) = &post.excerpt {
-            xml.push_str(&format!(
-                "      <description>{}</description>\n",
-                xml_escape(excerpt)
-            ));
-        }
-        for tag in &post.tags {
-            xml.push_str(&format!("      <category>{}</category>\n", xml_escape(tag)));
-        }
-        xml.push_str("    </item>\n");
+    let sitemap_path = output_dir.join("sitemap.xml");
+    if entries.len() <= SITEMAP_MAX_URLS {
+        write_file(&sitemap_path, urlset_xml(site_url, &entries))?;
+        tracing::info!("已生成 sitemap.xml（{} 个 URL）", entries.len());
+        return Ok(vec![sitemap_path]);
     }
 
-    xml.push_str("  </channel>\n</rss>\n");
-    std::fs::write(output_dir.join("feed.xml"), xml)?;
-    tracing::info!("已生成 RSS feed");
-    Ok(())
-}
+    let mut written = Vec::new();
+    let mut index = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    index.push_str("<sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+    for (i, chunk) in entries.chunks(SITEMAP_MAX_URLS).enumerate() {
+        let name = format!("sitemap-{}.xml", i + 1);
+        let path = output_dir.join(&name);
+        write_file(&path, urlset_xml(site_url, chunk))?;
+        written.push(path);
 
-fn generate_atom(output_dir: &Path, config: &SiteConfig, posts: &[Post]) -> Result<()> {
-    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
-    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
-    xml.push_str(&format!("  <title>{}</title>\n", xml_escape(&config.site.title)));
-    xml.push_str(&format!("  <link href=\"{}\" />\n", xml_escape(&config.site.url)));
-    xml.push_str(&format!(
-        "  <link href=\"{}/atom.xml\" rel=\"self\" type=\"application/atom+xml\" />\n",
-        xml_escape(&config.site.url)
-    ));
-    xml.push_str(&format!("  <id>{}/</id>\n", xml_escape(&config.site.url)));
-
-    if !config.site.author.name.is_empty() {
-        xml.push_str("  <author>\n");
-        xml.push_str(&format!(
-            "    <name>{}</name>\n",
-            xml_escape(&config.site.author.name)
+        index.push_str("  <sitemap>\n");
+        index.push_str(&format!(
+            "    <loc>{}/{}</loc>\n",
+            xml_escape(site_url),
+            name
         ));
-        if !config.site.author.email.is_empty() {
-            xml.push_str(&format!(
-                "    <email>{}</email>\n",
-                xml_escape(&config.site.author.email)
-            ));
+        if let Some(lastmod) = chunk.iter().filter_map(|e| e.lastmod).max() {
+            index.push_str(&format!("    <lastmod>{}</lastmod>\n", lastmod.format("%Y-%m-%d")));
         }
-        xml.push_str("  </author>\n");
+        index.push_str("  </sitemap>\n");
     }
+    index.push_str("</sitemapindex>\n");
+    write_file(&sitemap_path, index)?;
+    written.push(sitemap_path);
 
-    if let Some(post) = posts.first() {
-        xml.push_str(&format!(
-            "  <updated>{}</updated>\n",
-            post.updated_at.to_rfc3339()
-        ));
+    tracing::info!(
+        "已生成 sitemap 索引（{} 个 URL，拆分为 {} 个文件）",
+        entries.len(),
+        written.len() - 1
+    );
+    Ok(written)
+}
+
+/// 页面类型对应的 change_freq 与 priority；主题特殊页面（404 等）不列入 sitemap，返回 None
+fn sitemap_settings<'a>(config: &'a SiteConfig, page_type: &str) -> Option<(&'a str, f32)> {
+    let sitemap = &config.sitemap;
+    let types = &sitemap.types;
+    let (overrides, default_priority) = match page_type {
+        "index" => (&types.index, 1.0),
+        "post" => (&types.post, sitemap.priority),
+        "page" => (&types.page, sitemap.priority),
+        "tag" | "tags" => (&types.tag, 0.5),
+        "category" | "categories" => (&types.category, 0.5),
+        "archive" | "archive-year" | "archives" => (&types.archive, 0.5),
+        _ => return None,
+    };
+    let overrides = overrides.as_ref();
+    Some((
+        overrides
+            .and_then(|o| o.change_freq.as_deref())
+            .unwrap_or(&sitemap.change_freq),
+        overrides.and_then(|o| o.priority).unwrap_or(default_priority),
+    ))
+}
+
+fn urlset_xml(site_url: &str, entries: &[SitemapEntry]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\"");
+    if entries.iter().any(|e| !e.images.is_empty()) {
+        xml.push_str(" xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\"");
     }
+    xml.push_str(">\n");
 
-    for post in posts {
-        xml.push_str("  <entry>\n");
-        xml.push_str(&format!("    <title>{}</title>\n", xml_escape(&post.title)));
-        xml.push_str(&format!(
-            "    <link href=\"{}/posts/{}/\" />\n",
-            xml_escape(&config.site.url),
-            post.slug
-        ));
+    for entry in entries {
+        xml.push_str("  <url>\n");
         xml.push_str(&format!(
-            "    <id>{}/posts/{}/</id>\n",
-            xml_escape(&config.site.url),
-            post.slug
+            "    <loc>{}{}</loc>\n",
+            xml_escape(site_url),
+            xml_escape(entry.loc)
         ));
-        xml.push_str(&format!(
-            "    <published>{}</published>\n",
-            post.created_at.to_rfc3339()
-        ));
-        xml.push_str(&format!(
-            "    <updated>{}</updated>\n",
-            post.updated_at.to_rfc3339()
-        ));
-        if let Some(excerpt) = &post.excerpt {
-            xml.push_str(&format!(
-                "    <summary>{}</summary>\n",
-                xml_escape(excerpt)
-            ));
+        if let Some(lastmod) = entry.lastmod {
+            xml.push_str(&format!("    <lastmod>{}</lastmod>\n", lastmod.format("%Y-%m-%d")));
         }
-        if config.feed.full_content {
+        xml.push_str(&format!("    <changefreq>{}</changefreq>\n", entry.change_freq));
+        xml.push_str(&format!("    <priority>{:.1}</priority>\n", entry.priority));
+        for image in &entry.images {
             xml.push_str(&format!(
-                "    <content type=\"html\">{}</content>\n",
-                xml_escape(post.content.html())
+                "    <image:image>\n      <image:loc>{}</image:loc>\n    </image:image>\n",
+                xml_escape(image)
             ));
         }
-        xml.push_str("  </entry>\n");
+        xml.push_str("  </url>\n");
     }
 
-    xml.push_str("</feed>\n");
-    std::fs::write(output_dir.join("atom.xml"), xml)?;
-    tracing::info!("已生成 Atom feed");
-    Ok(())
+    xml.push_str("</urlset>\n");
+    xml
+}
+
+/// 正文中 `<img>` 引用的图片地址
+fn inline_images(html: &str) -> Vec<String> {
+    IMG_SRC_RE
+        .captures_iter(html)
+        .map(|cap| cap[1].replace("&amp;", "&"))
+        .collect()
+}
+
+/// 将站内路径转为绝对 URL；data: 等无法访问的地址与相对路径返回 None
+pub fn absolute_url(site_url: &str, src: &str) -> Option<String> {
+    if src.starts_with("https://") || src.starts_with("http://") {
+        Some(src.to_owned())
+    } else if let Some(rest) = src.strip_prefix("//") {
+        let scheme = site_url.split_once("://").map_or("https", |(s, _)| s);
+        Some(format!("{scheme}://{rest}"))
+    } else if src.starts_with('/') {
+        Some(format!("{site_url}{src}"))
+    } else {
+        None
+    }
 }
 
-fn xml_escape(s: &str) -> String {
+pub fn xml_escape(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
         .replace('>', "&gt;")
@@ -216,16 +256,16 @@ fn generate_search_index(output_dir: &Path, config: &SiteConfig, posts: &[Post])
             serde_json::json!({
                 "id": post.id.to_string(),
                 "title": &post.title,
-                "url": format!("/posts/{}/", post.slug),
+                "url": &post.url,
                 "content": content,
-                "tags": &post.tags,
+                "tags": post.tag_names(),
                 "date": post.created_at.to_rfc3339(),
             })
         })
         .collect();
 
     let json = serde_json::to_string(&index)?;
-    std::fs::write(output_dir.join("search-index.json"), json)?;
+    write_file(&output_dir.join("search-index.json"), json)?;
     tracing::info!("已生成搜索索引：{} 篇文章", posts.len());
     Ok(())
 }
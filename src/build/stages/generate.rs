@@ -1,145 +1,575 @@
+use crate::build::routes::UrlResolver;
+use crate::build::stages::feed::{feed_dir, feed_links};
+use crate::build::stages::render::page_output_path;
 use crate::config::SiteConfig;
-use crate::content::{Post, TaxonomyIndex};
+use crate::content::{Page, Post, TaxonomyIndex, TermSummary};
+use crate::theme::config::SpecialPage;
 use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
 
 #[derive(Debug, Serialize)]
 pub struct RenderPage {
     pub url: String,
     pub template: String,
     pub context: serde_json::Value,
+    /// 页面内容依赖的键（有序）：`post:{slug}` 表示引用了该文章，
+    /// 其余为列表规模、计数等摘要。键列表变化说明成员或顺序变化，需要重新渲染
+    pub deps: Vec<String>,
 }
 
-/// 根据文章和分类索引生成所有需要渲染的页面
+/// 文章依赖键
+pub fn post_dep(slug: &str) -> String {
+    format!("post:{slug}")
+}
+
+/// 根据文章、独立页面、主题特殊页面和分类索引生成所有需要渲染的页面
+///
+/// `templates` 为当前主题提供的模板名（不含 .cbtml），
+/// 标签云、分类总览和年度/完整归档等可选页面仅在主题提供对应模板时生成。
 pub fn generate_pages(
     posts: &[Post],
+    standalone_pages: &[Page],
+    special_pages: &[SpecialPage],
     taxonomy: &TaxonomyIndex,
+    templates: &HashSet<String>,
     config: &SiteConfig,
 ) -> Vec<RenderPage> {
+    let resolver = UrlResolver::new(&config.routes)
+        .with_tag_slugs(
+            taxonomy
+                .tag_info
+                .values()
+                .map(|t| (t.name.as_str(), t.slug.as_str())),
+        )
+        .with_category_slugs(
+            taxonomy
+                .category_info
+                .values()
+                .map(|c| (c.name.as_str(), c.slug.as_str())),
+        );
     let mut pages = Vec::new();
 
-    // 文章页
-    for (i, post) in posts.iter().enumerate() {
-        let prev = if i + 1 < posts.len() { Some(post_to_ctx(&posts[i + 1])) } else { None };
-        let next = if i > 0 { Some(post_to_ctx(&posts[i - 1])) } else { None };
-
-        let template = post.template.clone().unwrap_or_else(|| "post".into());
-        let context = serde_json::json!({
-            "post": post_to_ctx(post),
-            "prev_post": prev,
-            "next_post": next,
-            "page": {
-                "title": post.title,
-                "description": post.excerpt,
-                "url": format!("/posts/{}/", post.slug),
-                "type": "post",
-            },
+    // 主题声明的特殊页面：仅使用站点上下文渲染一次；最先生成，URL 冲突时优先保留
+    for special in special_pages {
+        pages.push(RenderPage {
+            url: special.output.clone(),
+            template: special.template.clone(),
+            context: serde_json::json!({
+                "page": {
+                    "title": special.title,
+                    "url": special.output,
+                    "type": special.template,
+                },
+            }),
+            deps: Vec::new(),
         });
+    }
 
+    // 文章页
+    pages.extend((0..posts.len()).map(|i| post_page(posts, i)));
+
+    // 独立页面：使用页面指定的模板，未指定时回退到 page
+    for page in standalone_pages {
+        let template = page.template.clone().unwrap_or_else(|| "page".into());
         pages.push(RenderPage {
-            url: format!("/posts/{}/", post.slug),
+            url: page.url.clone(),
             template,
-            context,
+            context: serde_json::json!({
+                "page": {
+                    "id": page.id.to_string(),
+                    "slug": page.slug,
+                    "title": page.title,
+                    "description": page.excerpt,
+                    "content": page.content.html(),
+                    "url": page.url,
+                    "created_at": page.created_at.to_rfc3339(),
+                    "updated_at": page.updated_at.to_rfc3339(),
+                    "type": "page",
+                },
+            }),
+            deps: Vec::new(),
         });
     }
 
     // 首页 + 分页
-    let per_page = config.build.posts_per_page;
-    let total_pages = ((posts.len() as f64) / per_page as f64).ceil() as usize;
-    let total_pages = total_pages.max(1);
-
-    for page_num in 1..=total_pages {
-        let start = (page_num - 1) * per_page;
-        let end = (start + per_page).min(posts.len());
-        let page_posts: Vec<_> = posts[start..end].iter().map(post_to_ctx).collect();
-
-        let url = if page_num == 1 {
-            "/".into()
+    for chunk in paginate(posts, config.build.posts_per_page, "/") {
+        let title = if chunk.current == 1 {
+            config.site.title.clone()
         } else {
-            format!("/page/{}/", page_num)
+            format!("第 {} 页", chunk.current)
         };
-
-        let pagination = serde_json::json!({
-            "current": page_num,
-            "total_pages": total_pages,
-            "total_posts": posts.len(),
-            "prev": if page_num > 1 { Some(if page_num == 2 { "/".into() } else { format!("/page/{}/", page_num - 1) }) } else { None::<String> },
-            "next": if page_num < total_pages { Some(format!("/page/{}/", page_num + 1)) } else { None::<String> },
-        });
-
         pages.push(RenderPage {
-            url,
+            url: chunk.url.clone(),
             template: "index".into(),
+            deps: listing_deps(chunk.items.iter().map(|p| p.slug.as_str()), posts.len(), []),
             context: serde_json::json!({
-                "posts": page_posts,
-                "pagination": pagination,
+                "posts": chunk.items.iter().map(post_to_ctx).collect::<Vec<_>>(),
+                "pagination": chunk.pagination,
                 "page": {
-                    "title": if page_num == 1 { config.site.title.clone() } else { format!("第 {} 页", page_num) },
+                    "title": title,
                     "description": &config.site.description,
-                    "url": if page_num == 1 { "/".into() } else { format!("/page/{}/", page_num) },
+                    "url": chunk.url,
                     "type": "index",
                 },
             }),
         });
     }
 
-    // 标签归档页
+    let overrides = &config.build.pagination;
+
+    // 标签归档页 + 分页
+    let tag_per_page = overrides.tag.unwrap_or(config.build.posts_per_page);
     for (tag, tag_posts) in &taxonomy.tags {
-        let slug = crate::cbtml::filters::filter_slugify(tag.clone());
+        let slug = resolver.tag_slug(tag);
+        let base_url = resolver.tag(&slug);
+        let info = taxonomy.tag_info.get(tag);
+        let description = info
+            .map(|t| t.description.clone())
+            .filter(|d| !d.is_empty())
+            .unwrap_or_else(|| format!("包含标签「{}」的所有文章", tag));
+        let tag_ctx = serde_json::json!({
+            "id": info.map(|t| t.id.as_str()).unwrap_or_default(),
+            "name": tag,
+            "slug": slug,
+            "description": info.map(|t| t.description.as_str()).unwrap_or_default(),
+            "url": base_url,
+            "count": tag_posts.len(),
+        });
+        let tag_feeds = if config.feed.per_tag {
+            feed_links(config, &feed_dir(&base_url), &format!("标签：{}", tag))
+        } else {
+            Vec::new()
+        };
+
+        for chunk in paginate(tag_posts, tag_per_page, &base_url) {
+            pages.push(RenderPage {
+                url: chunk.url.clone(),
+                template: "tag".into(),
+                deps: listing_deps(
+                    chunk.items.iter().map(|p| p.slug.as_str()),
+                    tag_posts.len(),
+                    [],
+                ),
+                context: serde_json::json!({
+                    "tag": tag,
+                    "tag_term": tag_ctx,
+                    "posts": chunk.items,
+                    "pagination": chunk.pagination,
+                    "page": {
+                        "title": paged_title(format!("标签：{}", tag), chunk.current),
+                        "description": description,
+                        "url": chunk.url,
+                        "type": "tag",
+                        "feeds": tag_feeds,
+                    },
+                }),
+            });
+        }
+    }
+
+    // 分类归档页 + 分页
+    let category_per_page = overrides.category.unwrap_or(config.build.posts_per_page);
+    for (cat, cat_posts) in &taxonomy.categories {
+        let slug = resolver.category_slug(cat);
+        let base_url = resolver.category(&slug);
+        let info = taxonomy.category_info.get(cat);
+        let description = info
+            .map(|i| i.description.clone())
+            .filter(|d| !d.is_empty())
+            .unwrap_or_else(|| format!("分类「{}」下的所有文章", cat));
+
+        // 面包屑：根分类 → 当前分类
+        let breadcrumbs: Vec<_> = taxonomy
+            .category_path(cat)
+            .iter()
+            .map(|name| {
+                serde_json::json!({
+                    "name": name,
+                    "url": resolver.category(&resolver.category_slug(name)),
+                })
+            })
+            .collect();
+
+        // 仅列出含有文章的子分类
+        let children: Vec<_> = info
+            .map(|i| i.children.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|name| {
+                let count = taxonomy.categories.get(name)?.len();
+                Some(serde_json::json!({
+                    "name": name,
+                    "url": resolver.category(&resolver.category_slug(name)),
+                    "count": count,
+                }))
+            })
+            .collect();
+
+        let children_deps: Vec<String> = children
+            .iter()
+            .map(|c| format!("child:{}:{}", c["name"], c["count"]))
+            .collect();
+
+        let category_ctx = serde_json::json!({
+            "id": info.map(|i| i.id.as_str()).unwrap_or_default(),
+            "name": cat,
+            "slug": slug,
+            "description": info.map(|i| i.description.as_str()).unwrap_or_default(),
+            "url": base_url,
+            "count": cat_posts.len(),
+        });
+        let category_feeds = if config.feed.per_category {
+            feed_links(config, &feed_dir(&base_url), &format!("分类：{}", cat))
+        } else {
+            Vec::new()
+        };
+
+        for chunk in paginate(cat_posts, category_per_page, &base_url) {
+            pages.push(RenderPage {
+                url: chunk.url.clone(),
+                template: "category".into(),
+                deps: listing_deps(
+                    chunk.items.iter().map(|p| p.slug.as_str()),
+                    cat_posts.len(),
+                    children_deps.iter().cloned(),
+                ),
+                context: serde_json::json!({
+                    "category": category_ctx,
+                    "breadcrumbs": breadcrumbs,
+                    "children": children,
+                    "posts": chunk.items,
+                    "pagination": chunk.pagination,
+                    "page": {
+                        "title": paged_title(format!("分类：{}", cat), chunk.current),
+                        "description": description,
+                        "url": chunk.url,
+                        "type": "category",
+                        "feeds": category_feeds,
+                    },
+                }),
+            });
+        }
+    }
+
+    // 时间归档页 + 分页
+    let archive_per_page = overrides.archive.unwrap_or(config.build.posts_per_page);
+    for ((year, month), archive_posts) in &taxonomy.archives {
+        let base_url = resolver.archive(*year, *month);
+        for chunk in paginate(archive_posts, archive_per_page, &base_url) {
+            pages.push(RenderPage {
+                url: chunk.url.clone(),
+                template: "archive".into(),
+                deps: listing_deps(
+                    chunk.items.iter().map(|p| p.slug.as_str()),
+                    archive_posts.len(),
+                    [],
+                ),
+                context: serde_json::json!({
+                    "year": year,
+                    "month": month,
+                    "posts": chunk.items,
+                    "pagination": chunk.pagination,
+                    "page": {
+                        "title": paged_title(format!("{}年{}月", year, month), chunk.current),
+                        "description": format!("{}年{}月的文章归档", year, month),
+                        "url": chunk.url,
+                        "type": "archive",
+                    },
+                }),
+            });
+        }
+    }
+
+    generate_index_pages(&mut pages, taxonomy, templates, &resolver);
+
+    // 不同来源的页面可能写入同一文件（如特殊页面与标签云都输出到 /tags/），保留先生成的页面
+    let mut emitted = HashSet::new();
+    pages.retain(|page| {
+        let unique = emitted.insert(page_output_path(Path::new(""), &page.url));
+        if !unique {
+            tracing::warn!("页面 URL 冲突，跳过模板 {} 生成的 {}", page.template, page.url);
+        }
+        unique
+    });
+
+    pages
+}
+
+/// 生成标签云、分类总览、年度归档和完整归档页，缺少模板的页面直接跳过
+fn generate_index_pages(
+    pages: &mut Vec<RenderPage>,
+    taxonomy: &TaxonomyIndex,
+    templates: &HashSet<String>,
+    resolver: &UrlResolver,
+) {
+    let term_ctx = |term: &TermSummary, slug: String, url: String| {
+        serde_json::json!({
+            "name": term.name,
+            "slug": slug,
+            "url": url,
+            "count": term.count,
+            "weight": term.weight,
+        })
+    };
+
+    // 总览页只展示名称与计数，依赖键由 (名称, 计数) 组成，文章内容变化不影响它们
+    if templates.contains("tags") {
+        let summaries = taxonomy.tag_summaries();
+        let deps = summaries
+            .iter()
+            .map(|t| format!("tag:{}:{}", t.name, t.count))
+            .collect();
+        let tags: Vec<_> = summaries
+            .iter()
+            .map(|t| {
+                let slug = resolver.tag_slug(&t.name);
+                let url = resolver.tag(&slug);
+                let mut ctx = term_ctx(t, slug, url);
+                ctx["description"] = taxonomy
+                    .tag_info
+                    .get(&t.name)
+                    .map(|i| i.description.clone())
+                    .unwrap_or_default()
+                    .into();
+                ctx
+            })
+            .collect();
+        let url = resolver.tag_index();
         pages.push(RenderPage {
-            url: format!("/tags/{}/", slug),
-            template: "tag".into(),
+            url: url.clone(),
+            template: "tags".into(),
+            deps,
             context: serde_json::json!({
-                "tag": tag,
-                "posts": tag_posts,
+                "tags": tags,
                 "page": {
-                    "title": format!("标签：{}", tag),
-                    "description": format!("包含标签「{}」的所有文章", tag),
-                    "url": format!("/tags/{}/", slug),
-                    "type": "tag",
+                    "title": "全部标签",
+                    "description": format!("共 {} 个标签", tags.len()),
+                    "url": url,
+                    "type": "tags",
                 },
             }),
         });
     }
 
-    // 分类归档页
-    for (cat, cat_posts) in &taxonomy.categories {
-        let slug = crate::cbtml::filters::filter_slugify(cat.clone());
+    if templates.contains("categories") {
+        let summaries = taxonomy.category_summaries();
+        let deps = summaries
+            .iter()
+            .map(|c| format!("category:{}:{}", c.name, c.count))
+            .collect();
+        let categories: Vec<_> = summaries
+            .iter()
+            .map(|c| {
+                let slug = resolver.category_slug(&c.name);
+                let url = resolver.category(&slug);
+                let mut ctx = term_ctx(c, slug, url);
+                let info = taxonomy.category_info.get(&c.name);
+                ctx["description"] = info.map(|i| i.description.clone()).unwrap_or_default().into();
+                ctx["parent"] = info.and_then(|i| i.parent.clone()).into();
+                ctx["depth"] = info.map(|i| i.ancestors.len()).unwrap_or_default().into();
+                ctx
+            })
+            .collect();
+        let url = resolver.category_index();
         pages.push(RenderPage {
-            url: format!("/category/{}/", slug),
-            template: "category".into(),
+            url: url.clone(),
+            template: "categories".into(),
+            deps,
             context: serde_json::json!({
-                "category": cat,
-                "posts": cat_posts,
+                "categories": categories,
                 "page": {
-                    "title": format!("分类：{}", cat),
-                    "description": format!("分类「{}」下的所有文章", cat),
-                    "url": format!("/category/{}/", slug),
-                    "type": "category",
+                    "title": "全部分类",
+                    "description": format!("共 {} 个分类", categories.len()),
+                    "url": url,
+                    "type": "categories",
                 },
             }),
         });
     }
 
-    // 时间归档页
-    for ((year, month), archive_posts) in &taxonomy.archives {
+    let has_year = templates.contains("archive-year");
+    let has_archives = templates.contains("archives");
+    if !has_year && !has_archives {
+        return;
+    }
+
+    let mut years = Vec::new();
+    let mut total_posts = 0;
+    let mut all_slugs: Vec<&str> = Vec::new();
+    for (year, months) in taxonomy.years() {
+        let count: usize = months.iter().map(|(_, posts)| posts.len()).sum();
+        let year_slugs: Vec<&str> = months
+            .iter()
+            .flat_map(|(_, posts)| posts.iter().map(|p| p.slug.as_str()))
+            .collect();
+        all_slugs.extend(&year_slugs);
+        let months: Vec<_> = months
+            .iter()
+            .map(|(month, posts)| {
+                serde_json::json!({
+                    "month": month,
+                    "url": resolver.archive(year, *month),
+                    "count": posts.len(),
+                    "posts": posts,
+                })
+            })
+            .collect();
+        total_posts += count;
+        let url = resolver.archive_year(year);
+
+        if has_year {
+            pages.push(RenderPage {
+                url: url.clone(),
+                template: "archive-year".into(),
+                deps: listing_deps(year_slugs.into_iter(), count, []),
+                context: serde_json::json!({
+                    "year": year,
+                    "months": months,
+                    "total_posts": count,
+                    "page": {
+                        "title": format!("{}年", year),
+                        "description": format!("{}年的文章归档", year),
+                        "url": url,
+                        "type": "archive-year",
+                    },
+                }),
+            });
+        }
+
+        years.push(serde_json::json!({
+            "year": year,
+            "url": url,
+            "count": count,
+            "months": months,
+        }));
+    }
+
+    if has_archives {
+        let url = resolver.archive_index();
         pages.push(RenderPage {
-            url: format!("/archive/{}/{:02}/", year, month),
-            template: "archive".into(),
+            url: url.clone(),
+            template: "archives".into(),
+            deps: listing_deps(all_slugs.into_iter(), total_posts, []),
             context: serde_json::json!({
-                "year": year,
-                "month": month,
-                "posts": archive_posts,
+                "years": years,
+                "total_posts": total_posts,
                 "page": {
-                    "title": format!("{}年{}月", year, month),
-                    "description": format!("{}年{}月的文章归档", year, month),
-                    "url": format!("/archive/{}/{:02}/", year, month),
-                    "type": "archive",
+                    "title": "文章归档",
+                    "description": format!("共 {} 篇文章", total_posts),
+                    "url": url,
+                    "type": "archives",
                 },
             }),
         });
     }
+}
+
+/// 列表页的依赖键：列表总数、附加摘要键，以及当页文章（保持顺序）
+fn listing_deps<'a>(
+    slugs: impl Iterator<Item = &'a str>,
+    total: usize,
+    extra: impl IntoIterator<Item = String>,
+) -> Vec<String> {
+    std::iter::once(format!("total:{total}"))
+        .chain(extra)
+        .chain(slugs.map(post_dep))
+        .collect()
+}
+
+/// 列表分页后的一页
+struct PageChunk<'a, T> {
+    current: usize,
+    url: String,
+    items: &'a [T],
+    pagination: serde_json::Value,
+}
+
+/// 按每页数量切分列表，第 1 页使用 `base_url`，其余页为 `{base_url}page/N/`；
+/// 空列表仍生成一页
+fn paginate<'a, T>(items: &'a [T], per_page: usize, base_url: &str) -> Vec<PageChunk<'a, T>> {
+    let per_page = per_page.max(1);
+    let total_pages = items.len().div_ceil(per_page).max(1);
+
+    (1..=total_pages)
+        .map(|current| {
+            let start = (current - 1) * per_page;
+            let end = (start + per_page).min(items.len());
+            let pagination = serde_json::json!({
+                "current": current,
+                "total_pages": total_pages,
+                "total_posts": items.len(),
+                "prev": (current > 1).then(|| paged_url(base_url, current - 1)),
+                "next": (current < total_pages).then(|| paged_url(base_url, current + 1)),
+            });
+            PageChunk {
+                current,
+                url: paged_url(base_url, current),
+                items: &items[start..end],
+                pagination,
+            }
+        })
+        .collect()
+}
+
+/// 第 N 页的 URL。以 `/` 结尾的基础 URL 直接追加 `page/N/`，
+/// 文件形式（如 `/tags/rust.html`）去掉扩展名后追加
+fn paged_url(base_url: &str, page_num: usize) -> String {
+    if page_num <= 1 {
+        return base_url.to_owned();
+    }
+    if base_url.ends_with('/') {
+        return format!("{base_url}page/{page_num}/");
+    }
+    let stem = match base_url.rsplit_once('.') {
+        Some((stem, _)) if !stem.ends_with('/') => stem,
+        _ => base_url,
+    };
+    format!("{stem}/page/{page_num}/")
+}
 
-    pages
+/// 非首页的列表标题附加页码
+fn paged_title(title: String, page_num: usize) -> String {
+    if page_num > 1 {
+        format!("{title}（第 {page_num} 页）")
+    } else {
+        title
+    }
+}
+
+/// 生成第 `i` 篇文章的页面，`posts` 按发布时间倒序，相邻文章作为上下篇
+pub fn post_page(posts: &[Post], i: usize) -> RenderPage {
+    let post = &posts[i];
+    let prev = posts.get(i + 1).map(post_to_ctx);
+    let next = i.checked_sub(1).map(|j| post_to_ctx(&posts[j]));
+
+    let template = post.template.clone().unwrap_or_else(|| "post".into());
+    let context = serde_json::json!({
+        "post": post_to_ctx(post),
+        "prev_post": prev,
+        "next_post": next,
+        "page": {
+            "title": post.title,
+            "description": post.excerpt,
+            "url": &post.url,
+            "type": "post",
+            "noindex": post.noindex,
+        },
+    });
+
+    // 文章页依赖自身与前后相邻文章
+    let deps = [posts.get(i + 1), Some(post), i.checked_sub(1).and_then(|j| posts.get(j))]
+        .into_iter()
+        .flatten()
+        .map(|p| post_dep(&p.slug))
+        .collect();
+
+    RenderPage {
+        url: post.url.clone(),
+        template,
+        context,
+        deps,
+    }
 }
 
 fn post_to_ctx(post: &Post) -> serde_json::Value {
@@ -150,14 +580,17 @@ fn post_to_ctx(post: &Post) -> serde_json::Value {
         "content": post.content.html(),
         "excerpt": post.excerpt,
         "cover_image": post.cover_image,
+        "noindex": post.noindex,
         "created_at": post.created_at.to_rfc3339(),
         "updated_at": post.updated_at.to_rfc3339(),
-        "tags": post.tags,
-        "category": post.category,
+        "tags": post.tag_names(),
+        "category": post.category_name(),
+        "tag_terms": post.tags,
+        "category_term": post.category,
         "author": post.author,
         "reading_time": post.reading_time,
         "word_count": post.word_count,
         "toc": post.toc,
-        "url": format!("/posts/{}/", post.slug),
+        "url": post.url,
     })
 }
@@ -0,0 +1,296 @@
+use crate::build::incremental::HashCache;
+use crate::build::release::write_file;
+use crate::build::stages::load::DbMedia;
+use crate::config::{ImageOptimizeConfig, ImageVariantFormat, SiteConfig};
+use crate::content::{Page, Post};
+use crate::media::process::{encode_variant, format_to_mime};
+use anyhow::{Context, Result};
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+use rayon::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// 派生图在输出目录中的子目录，对应 URL 前缀 `/_media/`
+pub const DERIVED_DIR: &str = "_media";
+
+/// 响应式图片清单文件名，写入输出目录的 _media/ 下
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// 缓存目录中以源文件哈希前缀作为派生图子目录名的长度
+const HASH_PREFIX_LEN: usize = 16;
+
+/// 正文与封面中引用的可缩放图片（GIF、SVG 不生成派生图）
+static MEDIA_IMAGE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"/media/[^\s"'()<>?#]+\.(?i:jpe?g|png|webp)"#).unwrap()
+});
+
+/// 同一格式的一组候选图
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageSource {
+    pub mime: String,
+    pub srcset: String,
+}
+
+/// 单张原图的尺寸与各格式的 srcset，按配置的格式顺序排列
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponsiveImage {
+    pub width: u32,
+    pub height: u32,
+    pub sources: Vec<ImageSource>,
+}
+
+/// 原图 URL（`/media/...`）到响应式信息的映射
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResponsiveImages(BTreeMap<String, ResponsiveImage>);
+
+impl ResponsiveImages {
+    /// 读取输出目录中的响应式图片清单，不存在时返回空清单
+    pub fn load(output_dir: &Path) -> Self {
+        std::fs::read_to_string(output_dir.join(DERIVED_DIR).join(MANIFEST_FILE))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn get(&self, url: &str) -> Option<&ResponsiveImage> {
+        self.0.get(url)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// 上次已生成的图片是否有派生信息变化或被移除，此时引用它们的已有页面需要重新渲染；
+    /// 新增的图片只会出现在本次变更的页面中，不计入
+    pub fn changed_since(&self, previous: &Self) -> bool {
+        previous
+            .0
+            .iter()
+            .any(|(url, image)| self.0.get(url) != Some(image))
+    }
+}
+
+/// 为文章与页面正文（含文章封面）引用的 /media/ 图片生成各宽度、各格式的派生图
+///
+/// 派生图按源文件哈希缓存在缓存目录的 images/ 下，源文件不变时不再重新编码；
+/// 返回写入输出目录的文件列表与响应式图片清单
+pub fn process_images(
+    project_root: &Path,
+    output_dir: &Path,
+    config: &SiteConfig,
+    posts: &[Post],
+    pages: &[Page],
+    db_media: &[DbMedia],
+) -> Result<(Vec<PathBuf>, ResponsiveImages)> {
+    let options = &config.features.image_optimize;
+    if !options.enabled || options.widths.is_empty() {
+        return Ok((Vec::new(), ResponsiveImages::default()));
+    }
+
+    let mut urls = BTreeSet::new();
+    let sources = posts
+        .iter()
+        .flat_map(|p| std::iter::once(p.content.html()).chain(p.cover_image.as_deref()))
+        .chain(pages.iter().map(|p| p.content.html()));
+    for text in sources {
+        urls.extend(MEDIA_IMAGE_RE.find_iter(text).map(|m| m.as_str().to_owned()));
+    }
+
+    let dimensions: HashMap<&str, (u32, u32)> = db_media
+        .iter()
+        .map(|m| (m.url.as_str(), (m.width, m.height)))
+        .collect();
+    let deriver = Deriver {
+        media_dir: project_root.join(&config.media.upload_dir),
+        cache_dir: project_root.join(&config.build.cache_dir).join("images"),
+        derived_out: output_dir.join(DERIVED_DIR),
+        options,
+    };
+
+    let results: Vec<Derived> = urls
+        .par_iter()
+        .filter_map(|url| match deriver.derive(url, dimensions.get(url.as_str()).copied()) {
+            Ok(derived) => derived,
+            Err(e) => {
+                tracing::warn!("生成响应式图片失败 {url}：{e:#}");
+                None
+            }
+        })
+        .collect();
+
+    let mut written = Vec::new();
+    let mut images = ResponsiveImages::default();
+    let mut used_keys = HashSet::new();
+    for derived in results {
+        written.extend(derived.files);
+        used_keys.insert(derived.key);
+        images.0.insert(derived.url, derived.image);
+    }
+
+    std::fs::create_dir_all(&deriver.derived_out)?;
+    let manifest_path = deriver.derived_out.join(MANIFEST_FILE);
+    write_file(&manifest_path, serde_json::to_string_pretty(&images)?)?;
+    written.push(manifest_path);
+
+    prune_cache(&deriver.cache_dir, &used_keys);
+    tracing::info!("已处理 {} 张响应式图片", images.0.len());
+    Ok((written, images))
+}
+
+struct Derived {
+    url: String,
+    /// 源文件哈希前缀，同时是缓存与输出中的子目录名
+    key: String,
+    image: ResponsiveImage,
+    files: Vec<PathBuf>,
+}
+
+struct Deriver<'a> {
+    media_dir: PathBuf,
+    cache_dir: PathBuf,
+    derived_out: PathBuf,
+    options: &'a ImageOptimizeConfig,
+}
+
+impl Deriver<'_> {
+    /// 生成单张图片的派生图，源文件不存在时返回 None
+    fn derive(&self, url: &str, known: Option<(u32, u32)>) -> Result<Option<Derived>> {
+        let rel = url.trim_start_matches("/media/");
+        if rel.split('/').any(|s| s.is_empty() || s == "." || s == "..") {
+            return Ok(None);
+        }
+        let src = self.media_dir.join(rel);
+        if !src.is_file() {
+            return Ok(None);
+        }
+
+        let source_format = ImageFormat::from_path(&src)?;
+        let source_ext = src
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let stem = src
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("image")
+            .to_owned();
+        let key = HashCache::compute_hash(&src)?[..HASH_PREFIX_LEN].to_owned();
+        let (width, height) = match known {
+            Some(dims) => dims,
+            None => image::image_dimensions(&src)
+                .with_context(|| format!("读取图片尺寸失败: {}", src.display()))?,
+        };
+
+        let mut widths: Vec<u32> = self.options.widths.iter().copied().filter(|&w| w < width).collect();
+        widths.sort_unstable();
+        widths.dedup();
+
+        let mut decoded: Option<DynamicImage> = None;
+        let mut files = Vec::new();
+        let mut sources: Vec<ImageSource> = Vec::new();
+        for variant in &self.options.formats {
+            let (format, ext) = match variant {
+                ImageVariantFormat::Original => (source_format, source_ext.as_str()),
+                ImageVariantFormat::Webp => (ImageFormat::WebP, "webp"),
+                ImageVariantFormat::Jpeg => (ImageFormat::Jpeg, "jpg"),
+                ImageVariantFormat::Png => (ImageFormat::Png, "png"),
+            };
+            let mime = format_to_mime(Some(format));
+            if sources.iter().any(|s| s.mime == mime) {
+                continue;
+            }
+
+            let mut candidates = Vec::new();
+            for &w in &widths {
+                let name = format!("{stem}-{w}.{ext}");
+                files.push(self.variant(&src, &mut decoded, &key, &name, w, format)?);
+                candidates.push(format!("/{DERIVED_DIR}/{key}/{name} {w}w"));
+            }
+            // 最大宽度：与原图同格式时直接使用原图，否则转换一份原尺寸
+            if format == source_format {
+                candidates.push(format!("{url} {width}w"));
+            } else {
+                let name = format!("{stem}.{ext}");
+                files.push(self.variant(&src, &mut decoded, &key, &name, width, format)?);
+                candidates.push(format!("/{DERIVED_DIR}/{key}/{name} {width}w"));
+            }
+
+            sources.push(ImageSource {
+                mime,
+                srcset: candidates.join(", "),
+            });
+        }
+
+        Ok(Some(Derived {
+            url: url.to_owned(),
+            key,
+            image: ResponsiveImage {
+                width,
+                height,
+                sources,
+            },
+            files,
+        }))
+    }
+
+    /// 取得一张派生图：缓存中没有时解码原图（每张原图至多一次）并缩放编码，再复制到输出目录
+    fn variant(
+        &self,
+        src: &Path,
+        decoded: &mut Option<DynamicImage>,
+        key: &str,
+        name: &str,
+        width: u32,
+        format: ImageFormat,
+    ) -> Result<PathBuf> {
+        let cached = self.cache_dir.join(key).join(name);
+        if !cached.exists() {
+            let img = match decoded {
+                Some(img) => img,
+                None => decoded.insert(
+                    image::open(src).with_context(|| format!("无法解码图片: {}", src.display()))?,
+                ),
+            };
+            let data = if width < img.width() {
+                encode_variant(&img.resize(width, u32::MAX, FilterType::Lanczos3), format, self.options.quality)?
+            } else {
+                encode_variant(img, format, self.options.quality)?
+            };
+            if let Some(parent) = cached.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&cached, data)?;
+        }
+
+        let dest = self.derived_out.join(key).join(name);
+        if !dest.exists() {
+            if let Some(parent) = dest.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::copy(&cached, &dest)
+                .with_context(|| format!("复制派生图失败: {}", dest.display()))?;
+        }
+        Ok(dest)
+    }
+}
+
+/// 删除缓存中本次构建未用到的派生图（源文件已变更或不再被引用）
+fn prune_cache(cache_dir: &Path, used_keys: &HashSet<String>) {
+    let Ok(entries) = std::fs::read_dir(cache_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        if used_keys.contains(name.to_string_lossy().as_ref()) {
+            continue;
+        }
+        if let Err(e) = std::fs::remove_dir_all(entry.path()) {
+            tracing::warn!("清理派生图缓存失败 {}: {}", entry.path().display(), e);
+        }
+    }
+}
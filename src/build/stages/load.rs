@@ -1,7 +1,8 @@
+use crate::build::routes::UrlResolver;
 use crate::config::SiteConfig;
 use crate::content::excerpt;
 use crate::content::markdown;
-use crate::content::{MarkdownContent, Post, PostStatus};
+use crate::content::{MarkdownContent, Page, Post, PostStatus, Term};
 use chrono::DateTime;
 use std::collections::HashMap;
 use std::path::Path;
@@ -18,10 +19,194 @@ pub struct DbPost {
     pub created_at: String,
     pub updated_at: String,
     pub meta: serde_json::Value,
+    /// post_tags 关联表中的标签 ID
+    pub tag_ids: Vec<String>,
+    /// post_categories 关联表中的分类 ID
+    pub category_id: Option<String>,
+}
+
+/// 数据库独立页面的简化结构，与 DbPost 一同从异步上下文传入构建
+#[derive(Clone)]
+pub struct DbPage {
+    pub id: String,
+    pub slug: String,
+    pub title: String,
+    pub content: String,
+    pub template: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// 数据库分类的简化结构，用于构建分类层级
+#[derive(Clone)]
+pub struct DbCategory {
+    pub id: String,
+    pub name: String,
+    pub slug: String,
+    pub description: String,
+    pub parent_id: Option<String>,
+}
+
+impl From<crate::repository::category::Category> for DbCategory {
+    fn from(c: crate::repository::category::Category) -> Self {
+        Self {
+            id: c.id,
+            name: c.name,
+            slug: c.slug,
+            description: c.description,
+            parent_id: c.parent_id,
+        }
+    }
+}
+
+/// 媒体表中记录的原图尺寸
+#[derive(Clone)]
+pub struct DbMedia {
+    pub url: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl DbMedia {
+    /// 从 `(url, width, height)` 行构造，缺少尺寸的记录（非图片）返回 None
+    pub fn from_row((url, width, height): (String, Option<i64>, Option<i64>)) -> Option<Self> {
+        Some(Self {
+            url,
+            width: u32::try_from(width?).ok()?,
+            height: u32::try_from(height?).ok()?,
+        })
+    }
+}
+
+/// 数据库标签的简化结构
+#[derive(Clone)]
+pub struct DbTag {
+    pub id: String,
+    pub name: String,
+    pub slug: String,
+    pub description: String,
+}
+
+impl From<crate::repository::tag::Tag> for DbTag {
+    fn from(t: crate::repository::tag::Tag) -> Self {
+        Self {
+            id: t.id,
+            name: t.name,
+            slug: t.slug,
+            description: t.description,
+        }
+    }
+}
+
+/// 标签/分类表的查找索引：关联表 ID 优先，
+/// 没有关联记录的文章回退到旧版 meta 中的名称
+struct TermTables<'a> {
+    resolver: &'a UrlResolver,
+    tags_by_id: HashMap<&'a str, &'a DbTag>,
+    tags_by_name: HashMap<&'a str, &'a DbTag>,
+    categories_by_id: HashMap<&'a str, &'a DbCategory>,
+    categories_by_name: HashMap<&'a str, &'a DbCategory>,
+}
+
+impl<'a> TermTables<'a> {
+    fn new(resolver: &'a UrlResolver, tags: &'a [DbTag], categories: &'a [DbCategory]) -> Self {
+        Self {
+            resolver,
+            tags_by_id: tags.iter().map(|t| (t.id.as_str(), t)).collect(),
+            tags_by_name: tags.iter().map(|t| (t.name.as_str(), t)).collect(),
+            categories_by_id: categories.iter().map(|c| (c.id.as_str(), c)).collect(),
+            categories_by_name: categories.iter().map(|c| (c.name.as_str(), c)).collect(),
+        }
+    }
+
+    fn tags(&self, db_post: &DbPost) -> Vec<Term> {
+        let mut tags: Vec<Term> = if db_post.tag_ids.is_empty() {
+            legacy_names(db_post.meta["tags"].as_str().unwrap_or(""))
+                .map(|name| match self.tags_by_name.get(name) {
+                    Some(tag) => self.tag_term(tag),
+                    None => self.legacy_term(name, |slug| self.resolver.tag(slug)),
+                })
+                .collect()
+        } else {
+            db_post
+                .tag_ids
+                .iter()
+                .filter_map(|id| self.tags_by_id.get(id.as_str()))
+                .map(|tag| self.tag_term(tag))
+                .collect()
+        };
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+        tags
+    }
+
+    fn category(&self, db_post: &DbPost) -> Option<Term> {
+        match &db_post.category_id {
+            Some(id) => self
+                .categories_by_id
+                .get(id.as_str())
+                .map(|c| self.category_term(c)),
+            None => {
+                let name = db_post.meta["category"].as_str()?.trim();
+                if name.is_empty() {
+                    return None;
+                }
+                Some(match self.categories_by_name.get(name) {
+                    Some(c) => self.category_term(c),
+                    None => self.legacy_term(name, |slug| self.resolver.category(slug)),
+                })
+            }
+        }
+    }
+
+    fn tag_term(&self, tag: &DbTag) -> Term {
+        Term {
+            id: tag.id.clone(),
+            name: tag.name.clone(),
+            slug: tag.slug.clone(),
+            description: tag.description.clone(),
+            url: self.resolver.tag(&tag.slug),
+        }
+    }
+
+    fn category_term(&self, category: &DbCategory) -> Term {
+        Term {
+            id: category.id.clone(),
+            name: category.name.clone(),
+            slug: category.slug.clone(),
+            description: category.description.clone(),
+            url: self.resolver.category(&category.slug),
+        }
+    }
+
+    /// 未登记到表中的旧版名称，slug 由名称生成
+    fn legacy_term(&self, name: &str, url: impl Fn(&str) -> String) -> Term {
+        let slug = crate::cbtml::filters::filter_slugify(name.to_owned());
+        Term {
+            id: String::new(),
+            name: name.to_owned(),
+            url: url(&slug),
+            slug,
+            description: String::new(),
+        }
+    }
+}
+
+/// 拆分旧版 meta 中逗号分隔的名称
+fn legacy_names(s: &str) -> impl Iterator<Item = &str> {
+    s.split(',').map(str::trim).filter(|s| !s.is_empty())
 }
 
 /// 从预取的数据库文章构建 Post 列表
-pub fn load_posts_from_db(db_posts: Vec<DbPost>, config: &SiteConfig) -> Vec<Post> {
+///
+/// 标签和分类以 post_tags / post_categories 关联表为准，永久链接由 `resolver` 解析。
+pub fn load_posts_from_db(
+    db_posts: Vec<DbPost>,
+    config: &SiteConfig,
+    resolver: &UrlResolver,
+    db_tags: &[DbTag],
+    db_categories: &[DbCategory],
+) -> Vec<Post> {
+    let terms = TermTables::new(resolver, db_tags, db_categories);
     let mut posts = Vec::new();
 
     for db_post in db_posts {
@@ -39,18 +224,8 @@ pub fn load_posts_from_db(db_posts: Vec<DbPost>, config: &SiteConfig) -> Vec<Pos
             .map(|dt| dt.with_timezone(&chrono::Utc))
             .unwrap_or(created_at);
 
-        let tags: Vec<String> = db_post.meta["tags"]
-            .as_str()
-            .unwrap_or("")
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect();
-
-        let category = db_post.meta["category"]
-            .as_str()
-            .filter(|s| !s.is_empty())
-            .map(|s| s.to_string());
+        let tags = terms.tags(&db_post);
+        let category = terms.category(&db_post);
 
         let cover_image = db_post.meta["cover_image"]
             .as_str()
@@ -62,6 +237,8 @@ pub fn load_posts_from_db(db_posts: Vec<DbPost>, config: &SiteConfig) -> Vec<Pos
             .filter(|s| !s.is_empty())
             .map(|s| s.to_string());
 
+        let noindex = db_post.meta["noindex"].as_bool().unwrap_or(false);
+
         let html_content = &db_post.content;
 
         let word_count = markdown::count_words_html(html_content);
@@ -72,9 +249,10 @@ pub fn load_posts_from_db(db_posts: Vec<DbPost>, config: &SiteConfig) -> Vec<Pos
         let md_content = MarkdownContent::new(String::new());
         md_content.set_html(db_post.content);
 
-        let post = Post {
+        let mut post = Post {
             id,
             slug: db_post.slug,
+            url: String::new(),
             title: db_post.title,
             content: md_content,
             status: PostStatus::Published,
@@ -84,6 +262,7 @@ pub fn load_posts_from_db(db_posts: Vec<DbPost>, config: &SiteConfig) -> Vec<Pos
             category,
             cover_image,
             excerpt: Some(meta_excerpt.unwrap_or(auto_excerpt)),
+            noindex,
             author: None,
             template: None,
             layout: None,
@@ -92,17 +271,20 @@ pub fn load_posts_from_db(db_posts: Vec<DbPost>, config: &SiteConfig) -> Vec<Pos
             toc,
             meta: HashMap::new(),
         };
+        post.url = resolver.post(&post);
 
         posts.push(post);
     }
 
     // 按创建时间降序排列
-    posts.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    posts.sort_by_key(|p| std::cmp::Reverse(p.created_at));
     posts
 }
 
 /// 同步从数据库预取发布状态的文章（用于 CLI build 命令等无 async runtime 的场景）
-pub fn fetch_db_posts_sync(db_path: &Path) -> Vec<DbPost> {
+///
+/// `drafts`、`future` 对应 `cblog build --drafts/--future`，同时包含草稿或发布时间未到的文章
+pub fn fetch_db_posts_sync(db_path: &Path, drafts: bool, future: bool) -> Vec<DbPost> {
     if !db_path.exists() {
         return Vec::new();
     }
@@ -117,29 +299,188 @@ pub fn fetch_db_posts_sync(db_path: &Path) -> Vec<DbPost> {
         let Ok(pool) = sqlx::SqlitePool::connect(&db_url).await else {
             return Vec::new();
         };
-        let rows = sqlx::query(
-            "SELECT id, slug, title, content, status, created_at, updated_at, meta FROM posts WHERE status = 'published'"
-        )
-        .fetch_all(&pool)
-        .await
-        .unwrap_or_default();
-
-        use sqlx::Row;
-        rows.into_iter()
-            .map(|row| {
-                let meta_str: String = row.get("meta");
-                let meta: serde_json::Value = serde_json::from_str(&meta_str).unwrap_or_default();
-                DbPost {
-                    id: row.get("id"),
-                    slug: row.get("slug"),
-                    title: row.get("title"),
-                    content: row.get("content"),
-                    status: row.get("status"),
-                    created_at: row.get("created_at"),
-                    updated_at: row.get("updated_at"),
-                    meta,
-                }
-            })
+        crate::repository::post::PostRepository::new(pool)
+            .fetch_for_build(drafts, future)
+            .await
+            .iter()
+            .map(db_post_from_row)
+            .collect()
+    })
+}
+
+/// 将 `PostRepository::fetch_published` 的查询行转换为 DbPost
+pub fn db_post_from_row(row: &sqlx::sqlite::SqliteRow) -> DbPost {
+    use sqlx::Row;
+    let meta_str: String = row.get("meta");
+    let meta: serde_json::Value = serde_json::from_str(&meta_str).unwrap_or_default();
+    let tag_ids: Option<String> = row.get("tag_ids");
+    DbPost {
+        id: row.get("id"),
+        slug: row.get("slug"),
+        title: row.get("title"),
+        content: row.get("content"),
+        status: row.get("status"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        meta,
+        tag_ids: tag_ids
+            .unwrap_or_default()
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .map(str::to_owned)
+            .collect(),
+        category_id: row.get("category_id"),
+    }
+}
+
+/// 从预取的数据库页面构建 Page 列表
+pub fn load_pages_from_db(db_pages: Vec<DbPage>, config: &SiteConfig) -> Vec<Page> {
+    let resolver = UrlResolver::new(&config.routes);
+
+    db_pages
+        .into_iter()
+        .map(|db_page| {
+            let id = db_page.id.parse::<Ulid>().unwrap_or_else(|_| Ulid::new());
+
+            let created_at = DateTime::parse_from_rfc3339(&db_page.created_at)
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .unwrap_or_else(|_| chrono::Utc::now());
+
+            let updated_at = DateTime::parse_from_rfc3339(&db_page.updated_at)
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .unwrap_or(created_at);
+
+            let excerpt = excerpt::extract_excerpt(&db_page.content, config.build.excerpt_length);
+
+            let content = MarkdownContent::new(String::new());
+            content.set_html(db_page.content);
+
+            Page {
+                id,
+                url: resolver.page(&db_page.slug),
+                slug: db_page.slug,
+                title: db_page.title,
+                content,
+                excerpt,
+                status: PostStatus::Published,
+                template: db_page.template.filter(|t| !t.trim().is_empty()),
+                created_at,
+                updated_at,
+            }
+        })
+        .collect()
+}
+
+/// 同步从数据库预取发布状态的独立页面，`drafts` 为 true 时同时包含草稿
+pub fn fetch_db_pages_sync(db_path: &Path, drafts: bool) -> Vec<DbPage> {
+    if !db_path.exists() {
+        return Vec::new();
+    }
+    let Ok(rt) = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    else {
+        return Vec::new();
+    };
+    rt.block_on(async {
+        let db_url = format!("sqlite:{}?mode=ro", db_path.display());
+        let Ok(pool) = sqlx::SqlitePool::connect(&db_url).await else {
+            return Vec::new();
+        };
+        crate::repository::page::PageRepository::new(pool)
+            .fetch_for_build(drafts)
+            .await
+            .iter()
+            .map(db_page_from_row)
+            .collect()
+    })
+}
+
+/// 将 pages 表的查询行转换为 DbPage
+pub fn db_page_from_row(row: &sqlx::sqlite::SqliteRow) -> DbPage {
+    use sqlx::Row;
+    DbPage {
+        id: row.get("id"),
+        slug: row.get("slug"),
+        title: row.get("title"),
+        content: row.get("content"),
+        template: row.get("template"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+/// 同步从数据库预取全部分类
+pub fn fetch_db_categories_sync(db_path: &Path) -> Vec<DbCategory> {
+    if !db_path.exists() {
+        return Vec::new();
+    }
+    let Ok(rt) = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    else {
+        return Vec::new();
+    };
+    rt.block_on(async {
+        let db_url = format!("sqlite:{}?mode=ro", db_path.display());
+        let Ok(pool) = sqlx::SqlitePool::connect(&db_url).await else {
+            return Vec::new();
+        };
+        crate::repository::category::CategoryRepository::new(pool)
+            .list_all()
+            .await
+            .into_iter()
+            .map(DbCategory::from)
+            .collect()
+    })
+}
+
+/// 同步从数据库预取全部标签
+pub fn fetch_db_tags_sync(db_path: &Path) -> Vec<DbTag> {
+    if !db_path.exists() {
+        return Vec::new();
+    }
+    let Ok(rt) = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    else {
+        return Vec::new();
+    };
+    rt.block_on(async {
+        let db_url = format!("sqlite:{}?mode=ro", db_path.display());
+        let Ok(pool) = sqlx::SqlitePool::connect(&db_url).await else {
+            return Vec::new();
+        };
+        crate::repository::tag::TagRepository::new(pool)
+            .list_all()
+            .await
+            .into_iter()
+            .map(DbTag::from)
+            .collect()
+    })
+}
+
+/// 同步从数据库预取媒体尺寸
+pub fn fetch_db_media_sync(db_path: &Path) -> Vec<DbMedia> {
+    if !db_path.exists() {
+        return Vec::new();
+    }
+    let Ok(rt) = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    else {
+        return Vec::new();
+    };
+    rt.block_on(async {
+        let db_url = format!("sqlite:{}?mode=ro", db_path.display());
+        let Ok(pool) = sqlx::SqlitePool::connect(&db_url).await else {
+            return Vec::new();
+        };
+        crate::repository::MediaRepository::new(pool)
+            .list_dimensions()
+            .await
+            .into_iter()
+            .filter_map(DbMedia::from_row)
             .collect()
     })
 }
@@ -0,0 +1,199 @@
+use crate::build::incremental::HashCache;
+use crate::config::{MediaMode, SiteConfig};
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// 哈希缓存中已发布媒体文件的键前缀
+const CACHE_PREFIX: &str = "media:";
+
+/// 扫描媒体引用的输出文件类型
+const SCANNED_EXTS: [&str; 4] = ["html", "xml", "json", "css"];
+
+/// 输出文件中的媒体 URL（上传时生成的公开路径为 `/media/{year}/{month}/{file}`）
+static MEDIA_URL_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"/media/([^"'\s<>()?#,&\\]+)"#).unwrap());
+
+/// 将项目媒体目录按 [build] media_mode 发布到输出目录的 media/，返回写入的文件列表
+///
+/// 复制类模式按文件哈希增量同步，未变更且已存在于输出中的文件不再复制；
+/// 不再发布的文件由输出清单在构建结束时清理
+pub fn publish_media(
+    project_root: &Path,
+    output_dir: &Path,
+    config: &SiteConfig,
+    hash_cache: &mut HashCache,
+) -> Result<Vec<PathBuf>> {
+    let media_src = project_root.join(&config.media.upload_dir);
+    let media_dest = output_dir.join("media");
+    let mode = config.build.media_mode;
+
+    if !media_src.is_dir() {
+        hash_cache.retain_prefixed(CACHE_PREFIX, |_| false);
+        return Ok(Vec::new());
+    }
+
+    // 发布方式切换时先移除上次的符号链接或目录
+    if media_dest.is_symlink() {
+        std::fs::remove_file(&media_dest)?;
+    } else if mode == MediaMode::Symlink && media_dest.exists() {
+        std::fs::remove_dir_all(&media_dest)?;
+    }
+
+    let files = match mode {
+        MediaMode::Symlink => {
+            #[cfg(unix)]
+            {
+                hash_cache.retain_prefixed(CACHE_PREFIX, |_| false);
+                std::os::unix::fs::symlink(&media_src, &media_dest)?;
+                tracing::info!("已创建 media 符号链接");
+                return Ok(vec![media_dest]);
+            }
+            #[cfg(not(unix))]
+            {
+                tracing::warn!("当前平台不支持符号链接，media 改为复制");
+                list_files(&media_src)
+            }
+        }
+        MediaMode::Copy | MediaMode::Hardlink => list_files(&media_src),
+        MediaMode::Referenced => referenced_files(output_dir, &media_dest),
+    };
+
+    let hardlink = mode == MediaMode::Hardlink;
+    let mut written = Vec::new();
+    let mut synced = 0;
+    for rel in &files {
+        let src = media_src.join(rel);
+        if !src.is_file() {
+            tracing::debug!("引用的媒体文件不存在：{rel}");
+            continue;
+        }
+        let dest = media_dest.join(rel);
+        let key = format!("{CACHE_PREFIX}{rel}");
+        let hash = HashCache::compute_hash(&src)?;
+        let unchanged = dest.exists() && !hash_cache.has_changed(&key, &hash);
+        if unchanged && (!hardlink || same_file(&src, &dest)) {
+            written.push(dest);
+            continue;
+        }
+
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // 先删除再写入：目标可能是指向源文件的硬链接，直接覆盖会截断源文件
+        if dest.exists() {
+            std::fs::remove_file(&dest)?;
+        }
+        if !hardlink || std::fs::hard_link(&src, &dest).is_err() {
+            std::fs::copy(&src, &dest)
+                .with_context(|| format!("复制媒体文件失败: {}", src.display()))?;
+        }
+        hash_cache.update(key, hash);
+        synced += 1;
+        written.push(dest);
+    }
+
+    hash_cache.retain_prefixed(CACHE_PREFIX, |rel| files.contains(rel));
+    tracing::info!("已发布 {} 个媒体文件（更新 {}）", written.len(), synced);
+    Ok(written)
+}
+
+/// 两个路径是否指向同一个文件（硬链接）
+fn same_file(a: &Path, b: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        match (std::fs::metadata(a), std::fs::metadata(b)) {
+            (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+            _ => false,
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (a, b);
+        true
+    }
+}
+
+/// 媒体目录下的全部文件（相对路径）
+fn list_files(media_src: &Path) -> BTreeSet<String> {
+    fn walk(base: &Path, dir: &Path, files: &mut BTreeSet<String>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                walk(base, &path, files);
+            } else if let Ok(rel) = path.strip_prefix(base) {
+                files.insert(rel.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+
+    let mut files = BTreeSet::new();
+    walk(media_src, media_src, &mut files);
+    files
+}
+
+/// 扫描输出目录中的页面、订阅与样式文件，收集其中引用的媒体文件（相对路径）
+fn referenced_files(output_dir: &Path, media_dest: &Path) -> BTreeSet<String> {
+    fn walk(dir: &Path, media_dest: &Path, files: &mut BTreeSet<String>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                if path != media_dest {
+                    walk(&path, media_dest, files);
+                }
+                continue;
+            }
+            if !path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| SCANNED_EXTS.contains(&e))
+            {
+                continue;
+            }
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            for cap in MEDIA_URL_RE.captures_iter(&content) {
+                let rel = percent_decode(&cap[1]);
+                // 拒绝 `..` 等路径片段，避免把媒体目录之外的文件发布出去
+                if rel.split('/').all(|s| !s.is_empty() && s != "." && s != "..") {
+                    files.insert(rel);
+                }
+            }
+        }
+    }
+
+    let mut files = BTreeSet::new();
+    walk(output_dir, media_dest, &mut files);
+    files
+}
+
+/// 解码 URL 中的 `%XX` 转义，非法转义原样保留
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(byte) = s
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            out.push(byte);
+            i += 3;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
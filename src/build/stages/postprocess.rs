@@ -1,3 +1,4 @@
+use crate::build::stages::images::ResponsiveImages;
 use crate::config::SiteConfig;
 use regex::Regex;
 use std::sync::LazyLock;
@@ -5,6 +6,13 @@ use std::sync::LazyLock;
 static IMG_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"<img([^>]*?)(/?>)").unwrap());
 
+static SRC_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\ssrc="([^"]*)""#).unwrap());
+
+/// 标签中的属性名（`data-width=` 的属性名是 data-width 而不是 width）
+static ATTR_NAME_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|\s)([^\s=/>]+)\s*=").unwrap());
+
 const SYNTAX_HIGHLIGHT_CSS: &str = r#"<style>
 .code-highlight { background: #282c34; color: #abb2bf; padding: 16px; border-radius: 6px; overflow-x: auto; }
 .code-highlight code { background: none; padding: 0; }
@@ -29,12 +37,56 @@ html { scroll-behavior: smooth; }
 .toc-list a:hover { text-decoration: underline; }
 </style>"#;
 
+/// `cblog dev` 实时刷新 WebSocket 路径
+pub const LIVE_RELOAD_PATH: &str = "/__cblog/livereload";
+
+/// 开发模式注入的刷新脚本：收到 reload 刷新页面，收到 error 显示错误浮层，断线后自动重连
+const LIVE_RELOAD_SCRIPT: &str = r#"<script>
+(function () {
+  var overlay = null;
+  function hideError() {
+    if (overlay) { overlay.remove(); overlay = null; }
+  }
+  function showError(message) {
+    hideError();
+    overlay = document.createElement('div');
+    overlay.style.cssText = 'position:fixed;inset:0;z-index:2147483647;background:rgba(20,20,24,.92);color:#f8f8f2;padding:32px;overflow:auto;font:14px/1.6 ui-monospace,Menlo,Consolas,monospace;';
+    var title = document.createElement('div');
+    title.style.cssText = 'color:#ff6b6b;font-size:18px;font-weight:bold;margin-bottom:16px;';
+    title.textContent = 'cblog 构建失败';
+    var close = document.createElement('button');
+    close.textContent = '×';
+    close.style.cssText = 'position:absolute;top:16px;right:24px;background:none;border:0;color:#f8f8f2;font-size:24px;cursor:pointer;';
+    close.onclick = hideError;
+    var pre = document.createElement('pre');
+    pre.style.cssText = 'white-space:pre-wrap;margin:0;';
+    pre.textContent = message;
+    overlay.append(close, title, pre);
+    document.body.appendChild(overlay);
+  }
+  function connect() {
+    var proto = location.protocol === 'https:' ? 'wss://' : 'ws://';
+    var ws = new WebSocket(proto + location.host + '__PATH__');
+    ws.onmessage = function (e) {
+      var ev = JSON.parse(e.data);
+      if (ev.type === 'reload') location.reload();
+      else if (ev.type === 'error') showError(ev.message);
+    };
+    ws.onclose = function () { setTimeout(connect, 1000); };
+  }
+  connect();
+})();
+</script>"#;
+
 /// 对渲染后的 HTML 进行后处理（写入磁盘前）
-pub fn apply(html: String, config: &SiteConfig) -> String {
+pub fn apply(html: String, config: &SiteConfig, images: &ResponsiveImages) -> String {
     let mut html = html;
 
     if config.features.image_optimize.enabled {
         html = add_lazy_loading(html);
+        if !images.is_empty() {
+            html = add_responsive_images(html, images, &config.features.image_optimize.sizes);
+        }
     }
 
     // CSS 注入：syntax-highlight + toc，合并为一次 </head> 替换
@@ -51,6 +103,14 @@ pub fn apply(html: String, config: &SiteConfig) -> String {
         html = html.replacen("</head>", &head_inject, 1);
     }
 
+    if config.build.live_reload {
+        let script = LIVE_RELOAD_SCRIPT.replace("__PATH__", LIVE_RELOAD_PATH);
+        html = match html.rfind("</body>") {
+            Some(pos) => format!("{}{script}{}", &html[..pos], &html[pos..]),
+            None => html + &script,
+        };
+    }
+
     html
 }
 
@@ -60,7 +120,7 @@ fn add_lazy_loading(html: String) -> String {
         .replace_all(&html, |caps: &regex::Captures| {
             let attrs = &caps[1];
             let close = &caps[2];
-            if attrs.contains("loading=") {
+            if has_attr(attrs, "loading") {
                 format!("<img{attrs}{close}")
             } else {
                 format!("<img loading=\"lazy\"{attrs}{close}")
@@ -68,3 +128,52 @@ fn add_lazy_loading(html: String) -> String {
         })
         .into_owned()
 }
+
+/// 为引用了已生成派生图的 <img> 写入 srcset、sizes 与原图的 width、height，
+/// 配置了多种格式时包裹为 <picture>，前面的格式以 <source> 提供
+fn add_responsive_images(html: String, images: &ResponsiveImages, sizes: &str) -> String {
+    let sizes = sizes.replace('"', "&quot;");
+    IMG_RE
+        .replace_all(&html, |caps: &regex::Captures| {
+            let attrs = &caps[1];
+            let close = &caps[2];
+            let Some(image) = SRC_RE
+                .captures(attrs)
+                .and_then(|c| images.get(&c[1]))
+                .filter(|_| !has_attr(attrs, "srcset"))
+            else {
+                return format!("<img{attrs}{close}");
+            };
+            let Some((fallback, preferred)) = image.sources.split_last() else {
+                return format!("<img{attrs}{close}");
+            };
+
+            let mut img = format!("<img{}", attrs.trim_end());
+            if !has_attr(attrs, "width") && !has_attr(attrs, "height") {
+                img.push_str(&format!(" width=\"{}\" height=\"{}\"", image.width, image.height));
+            }
+            img.push_str(&format!(" srcset=\"{}\" sizes=\"{sizes}\"{close}", fallback.srcset));
+            if preferred.is_empty() {
+                return img;
+            }
+
+            let mut picture = String::from("<picture>");
+            for source in preferred {
+                picture.push_str(&format!(
+                    "<source type=\"{}\" srcset=\"{}\" sizes=\"{sizes}\">",
+                    source.mime, source.srcset
+                ));
+            }
+            picture.push_str(&img);
+            picture.push_str("</picture>");
+            picture
+        })
+        .into_owned()
+}
+
+/// 属性串中是否含有指定名称的属性
+fn has_attr(attrs: &str, name: &str) -> bool {
+    ATTR_NAME_RE
+        .captures_iter(attrs)
+        .any(|c| c[1].eq_ignore_ascii_case(name))
+}
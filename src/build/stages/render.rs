@@ -1,136 +1,277 @@
 use crate::admin::settings::SiteSettings;
+use crate::build::events::{BuildEvent, EventSink};
+use crate::build::minify::Savings;
+use crate::build::profile::Profiler;
+use crate::build::release::write_file;
+use crate::build::routes::UrlResolver;
+use crate::build::stages::assets::AssetManifest;
 use crate::build::stages::generate::RenderPage;
+use crate::build::stages::images::ResponsiveImages;
 use crate::cbtml;
 use crate::config::SiteConfig;
 use anyhow::Result;
 use minijinja::Environment;
 use rayon::prelude::*;
-use std::collections::HashMap;
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Mutex;
 
-/// 渲染页面到 public/ 目录
-pub fn render_pages(
-    project_root: &Path,
-    config: &SiteConfig,
-    pages: &[&RenderPage],
-    theme_config: &HashMap<String, serde_json::Value>,
-    site_settings: &SiteSettings,
-) -> Result<()> {
-    let output_dir = project_root.join(&config.build.output_dir);
-    let themes_dir = project_root.join("themes");
-    let active_theme = &config.theme.active;
-
-    // 预编译所有 cbtml 模板为 MiniJinja 模板字符串
-    let compiled_templates = compile_all_templates(&themes_dir, active_theme)?;
+/// 单个页面的渲染失败记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderError {
+    pub url: String,
+    /// 出错的模板（页面模板，或其继承/引用的模板）
+    pub template: String,
+    /// 出错位置在 cbtml 源码中的行号
+    pub line: Option<usize>,
+    pub message: String,
+}
 
-    // DB 非空值优先，否则 fallback 到 toml
-    let site_url = if site_settings.site_url.is_empty() {
-        &config.site.url
-    } else {
-        &site_settings.site_url
-    };
+impl RenderError {
+    fn new(page: &RenderPage, template: &str, message: impl Into<String>) -> Self {
+        Self {
+            url: page.url.clone(),
+            template: template.to_owned(),
+            line: None,
+            message: message.into(),
+        }
+    }
 
-    let mut env = Environment::new();
-    cbtml::filters::register_filters(&mut env, site_url);
+    /// 从 MiniJinja 渲染错误提取出错模板与行号（编译产物行号已与 cbtml 源码对齐）
+    fn from_template_error(page: &RenderPage, template: &str, err: &minijinja::Error) -> Self {
+        let message = match err.detail() {
+            Some(detail) => format!("{}：{}", err.kind(), detail),
+            None => err.kind().to_string(),
+        };
+        Self {
+            url: page.url.clone(),
+            template: err.name().unwrap_or(template).to_owned(),
+            line: err.line(),
+            message,
+        }
+    }
+}
 
-    // 将编译后的模板逐个添加到环境中
-    for (name, source) in &compiled_templates {
-        env.add_template_owned(name.clone(), source.clone())?;
+impl std::fmt::Display for RenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}（{}:{}）：{}", self.url, self.template, line, self.message),
+            None => write!(f, "{}（{}）：{}", self.url, self.template, self.message),
+        }
     }
+}
 
-    let site_title = if site_settings.site_title.is_empty() {
-        &config.site.title
-    } else {
-        &site_settings.site_title
-    };
-    let site_subtitle = if site_settings.site_subtitle.is_empty() {
-        &config.site.subtitle
-    } else {
-        &site_settings.site_subtitle
-    };
-
-    let site_ctx = serde_json::json!({
-        "title": site_title,
-        "subtitle": site_subtitle,
-        "description": config.site.description,
-        "url": site_url,
-        "language": config.site.language,
-        "author": {
-            "name": config.site.author.name,
-            "email": config.site.author.email,
-            "avatar": config.site.author.avatar,
-            "bio": config.site.author.bio,
-        },
-    });
+/// 页面渲染所需的站点级参数
+pub struct RenderContext<'a> {
+    pub project_root: &'a Path,
+    pub output_dir: &'a Path,
+    pub config: &'a SiteConfig,
+    pub resolver: &'a UrlResolver,
+    pub theme_config: &'a HashMap<String, serde_json::Value>,
+    pub site_settings: &'a SiteSettings,
+    pub events: &'a EventSink,
+    pub profiler: &'a Profiler,
+    pub minify: &'a Savings,
+    pub assets: &'a AssetManifest,
+    pub images: &'a ResponsiveImages,
+}
 
-    let rendered_count = AtomicUsize::new(0);
-    let errors: Mutex<Vec<String>> = Mutex::new(Vec::new());
+/// 已载入主题模板与站点上下文的页面渲染器，供批量构建和单页预览共用
+pub struct PageRenderer<'a> {
+    env: Environment<'static>,
+    site_ctx: serde_json::Value,
+    theme_config: &'a HashMap<String, serde_json::Value>,
+    config: &'a SiteConfig,
+    images: &'a ResponsiveImages,
+}
 
-    pages.par_iter().for_each(|page| {
-        let template_name = format!("{}.cbtml", page.template);
-        let tmpl = match env.get_template(&template_name) {
-            Ok(t) => t,
-            Err(_) => {
-                tracing::warn!("模板 {} 不存在，跳过页面 {}", template_name, page.url);
-                return;
-            }
+impl<'a> PageRenderer<'a> {
+    pub fn new(
+        project_root: &Path,
+        config: &'a SiteConfig,
+        resolver: &UrlResolver,
+        theme_config: &'a HashMap<String, serde_json::Value>,
+        site_settings: &SiteSettings,
+        assets: &AssetManifest,
+        images: &'a ResponsiveImages,
+    ) -> Result<Self> {
+        let themes_dir = project_root.join("themes");
+        let active_theme = &config.theme.active;
+
+        // 预编译所有 cbtml 模板为 MiniJinja 模板字符串
+        let compiled_templates = compile_all_templates(&themes_dir, active_theme)?;
+
+        // DB 非空值优先，否则 fallback 到 toml
+        let site_url = if site_settings.site_url.is_empty() {
+            &config.site.url
+        } else {
+            &site_settings.site_url
+        };
+
+        let mut env = Environment::new();
+        cbtml::filters::register_filters(&mut env, site_url, resolver.clone());
+        let assets = assets.clone();
+        env.add_function("asset_url", move |name: String| -> String { assets.url(&name) });
+
+        // 将编译后的模板逐个添加到环境中
+        for (name, source) in compiled_templates {
+            env.add_template_owned(name, source)?;
+        }
+
+        let site_title = if site_settings.site_title.is_empty() {
+            &config.site.title
+        } else {
+            &site_settings.site_title
         };
+        let site_subtitle = if site_settings.site_subtitle.is_empty() {
+            &config.site.subtitle
+        } else {
+            &site_settings.site_subtitle
+        };
+
+        let site_ctx = serde_json::json!({
+            "title": site_title,
+            "subtitle": site_subtitle,
+            "description": config.site.description,
+            "url": site_url,
+            "language": config.site.language,
+            "feeds": super::feed::feed_links(config, "/", site_title),
+            // 索引页地址按 [routes] 解析，供导航等模板引用
+            "tag_index_url": resolver.tag_index(),
+            "category_index_url": resolver.category_index(),
+            "archive_index_url": resolver.archive_index(),
+            "author": {
+                "name": config.site.author.name,
+                "email": config.site.author.email,
+                "avatar": config.site.author.avatar,
+                "bio": config.site.author.bio,
+            },
+        });
+
+        Ok(Self {
+            env,
+            site_ctx,
+            theme_config,
+            config,
+            images,
+        })
+    }
+
+    /// 渲染单个页面并执行后处理，返回完整 HTML
+    pub fn render(&self, page: &RenderPage) -> std::result::Result<String, RenderError> {
+        let template_name = format!("{}.cbtml", page.template);
+        let tmpl = self
+            .env
+            .get_template(&template_name)
+            .map_err(|_| RenderError::new(page, &template_name, "模板不存在"))?;
 
         let mut ctx = page.context.as_object().cloned().unwrap_or_default();
-        ctx.insert("site".into(), site_ctx.clone());
-        let config_obj: serde_json::Map<String, serde_json::Value> =
-            theme_config.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        ctx.insert("site".into(), self.site_ctx.clone());
+        let config_obj: serde_json::Map<String, serde_json::Value> = self
+            .theme_config
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
         ctx.insert("config".into(), serde_json::Value::Object(config_obj));
         let ctx_value = minijinja::Value::from_serialize(&ctx);
 
-        let html = match tmpl.render(ctx_value) {
+        let html = tmpl
+            .render(ctx_value)
+            .map_err(|e| RenderError::from_template_error(page, &template_name, &e))?;
+
+        Ok(super::postprocess::apply(html, self.config, self.images))
+    }
+}
+
+/// 渲染页面到输出目录，返回渲染失败的页面
+pub fn render_pages(rctx: &RenderContext<'_>, pages: &[&RenderPage]) -> Result<Vec<RenderError>> {
+    let RenderContext {
+        project_root,
+        output_dir,
+        config,
+        resolver,
+        theme_config,
+        site_settings,
+        events,
+        profiler,
+        minify,
+        assets,
+        images,
+    } = *rctx;
+    let renderer = PageRenderer::new(
+        project_root,
+        config,
+        resolver,
+        theme_config,
+        site_settings,
+        assets,
+        images,
+    )?;
+
+    let rendered_count = AtomicUsize::new(0);
+    let errors: Mutex<Vec<RenderError>> = Mutex::new(Vec::new());
+
+    // 进度按约 1% 的步长推送，避免大站点时事件过多
+    let total = pages.len();
+    let step = (total / 100).max(1);
+    let processed = AtomicUsize::new(0);
+    events.progress(0, total);
+
+    pages.par_iter().for_each(|page| {
+        let _progress = ProgressGuard {
+            processed: &processed,
+            total,
+            step,
+            events,
+        };
+        let started = std::time::Instant::now();
+        let html = match renderer.render(page) {
+            Ok(html) if config.build.minify.html => minify.html(&html),
             Ok(html) => html,
-            Err(e) => {
-                let msg = format!(
-                    "渲染页面 {} 失败（模板：{}）：\n{}",
-                    page.url, template_name, e
-                );
-                tracing::error!("{}", msg);
-                errors.lock().unwrap().push(msg);
+            Err(err) => {
+                tracing::error!("渲染页面失败：{}", err);
+                errors.lock().unwrap().push(err);
                 return;
             }
         };
+        let template_name = format!("{}.cbtml", page.template);
 
-        let html = super::postprocess::apply(html, config);
-
-        let file_path = if page.url.ends_with('/') {
-            output_dir
-                .join(page.url.trim_start_matches('/'))
-                .join("index.html")
-        } else {
-            output_dir.join(page.url.trim_start_matches('/'))
-        };
+        let file_path = page_output_path(output_dir, &page.url);
 
         if let Some(parent) = file_path.parent()
             && let Err(e) = std::fs::create_dir_all(parent) {
-                errors
-                    .lock()
-                    .unwrap()
-                    .push(format!("创建目录失败 {}: {}", parent.display(), e));
+                errors.lock().unwrap().push(RenderError::new(
+                    page,
+                    &template_name,
+                    format!("创建目录失败 {}: {}", parent.display(), e),
+                ));
                 return;
             }
-        if let Err(e) = std::fs::write(&file_path, html) {
-            errors
-                .lock()
-                .unwrap()
-                .push(format!("写入文件失败 {}: {}", file_path.display(), e));
+        if let Err(e) = write_file(&file_path, html) {
+            errors.lock().unwrap().push(RenderError::new(
+                page,
+                &template_name,
+                format!("写入文件失败 {}: {}", file_path.display(), e),
+            ));
             return;
         }
 
+        profiler.record_page(&page.url, &page.template, started.elapsed());
         rendered_count.fetch_add(1, Ordering::Relaxed);
         tracing::debug!("已写入：{}", file_path.display());
     });
 
-    let errs = errors.into_inner().unwrap();
+    let mut errs = errors.into_inner().unwrap();
+    errs.sort_by(|a, b| a.url.cmp(&b.url));
     if !errs.is_empty() {
-        tracing::warn!("渲染过程中有 {} 个错误", errs.len());
+        for err in &errs {
+            events.emit(BuildEvent::Warning {
+                message: format!("渲染页面失败：{err}"),
+            });
+        }
+        events.warn(format!("渲染过程中有 {} 个页面失败", errs.len()));
     }
 
     tracing::info!(
@@ -138,7 +279,60 @@ pub fn render_pages(
         pages.len(),
         rendered_count.load(Ordering::Relaxed)
     );
-    Ok(())
+    Ok(errs)
+}
+
+/// 单个页面处理结束（无论成功与否）时计数并按步长推送渲染进度
+struct ProgressGuard<'a> {
+    processed: &'a AtomicUsize,
+    total: usize,
+    step: usize,
+    events: &'a EventSink,
+}
+
+impl Drop for ProgressGuard<'_> {
+    fn drop(&mut self) {
+        let done = self.processed.fetch_add(1, Ordering::Relaxed) + 1;
+        if done.is_multiple_of(self.step) || done == self.total {
+            self.events.progress(done, self.total);
+        }
+    }
+}
+
+/// 页面 URL 对应的输出文件：以 `/` 结尾的 URL 写入目录下的 index.html
+pub fn page_output_path(output_dir: &Path, url: &str) -> PathBuf {
+    if url.ends_with('/') {
+        output_dir.join(url.trim_start_matches('/')).join("index.html")
+    } else {
+        output_dir.join(url.trim_start_matches('/'))
+    }
+}
+
+/// 列出当前主题提供的模板名（相对 templates/ 的路径，不含 .cbtml 后缀）
+pub fn theme_template_names(project_root: &Path, active_theme: &str) -> HashSet<String> {
+    fn walk(base_dir: &Path, current_dir: &Path, names: &mut HashSet<String>) {
+        let Ok(entries) = std::fs::read_dir(current_dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                walk(base_dir, &path, names);
+            } else if path.extension().is_some_and(|ext| ext == "cbtml")
+                && let Ok(rel) = path.with_extension("").strip_prefix(base_dir)
+            {
+                names.insert(rel.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+
+    let template_dir = project_root
+        .join("themes")
+        .join(active_theme)
+        .join("templates");
+    let mut names = HashSet::new();
+    walk(&template_dir, &template_dir, &mut names);
+    names
 }
 
 /// 编译所有主题的模板。当前主题的模板以 `name.cbtml` 注册，
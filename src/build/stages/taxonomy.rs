@@ -1,10 +1,19 @@
+use crate::build::stages::load::DbCategory;
 use crate::config::SiteConfig;
-use crate::content::{Post, PostRef, TaxonomyIndex};
+use crate::content::{CategoryInfo, Post, PostRef, TaxonomyIndex, Term};
 use chrono::Datelike;
 use std::collections::HashMap;
 
 /// 构建标签、分类和时间归档索引
-pub fn build_taxonomy(posts: &[Post], _config: &SiteConfig) -> TaxonomyIndex {
+///
+/// 分类按分类表的 parent_id 组织为层级，文章同时计入所属分类的所有祖先分类。
+pub fn build_taxonomy(
+    posts: &[Post],
+    db_categories: &[DbCategory],
+    _config: &SiteConfig,
+) -> TaxonomyIndex {
+    let mut category_info = build_category_info(db_categories);
+    let mut tag_info: HashMap<String, Term> = HashMap::new();
     let mut tags: HashMap<String, Vec<PostRef>> = HashMap::new();
     let mut categories: HashMap<String, Vec<PostRef>> = HashMap::new();
     let mut archives: std::collections::BTreeMap<(i32, u32), Vec<PostRef>> =
@@ -14,16 +23,33 @@ pub fn build_taxonomy(posts: &[Post], _config: &SiteConfig) -> TaxonomyIndex {
         let post_ref = post_to_ref(post);
 
         for tag in &post.tags {
-            tags.entry(tag.clone())
+            tags.entry(tag.name.clone())
                 .or_default()
                 .push(post_ref.clone());
+            tag_info
+                .entry(tag.name.clone())
+                .or_insert_with(|| tag.clone());
         }
 
         if let Some(cat) = &post.category {
-            categories
-                .entry(cat.clone())
-                .or_default()
-                .push(post_ref.clone());
+            // 未登记到分类表的分类视为无父级的顶层分类
+            let info = category_info
+                .entry(cat.name.clone())
+                .or_insert_with(|| CategoryInfo {
+                    id: cat.id.clone(),
+                    name: cat.name.clone(),
+                    slug: cat.slug.clone(),
+                    description: cat.description.clone(),
+                    parent: None,
+                    children: Vec::new(),
+                    ancestors: Vec::new(),
+                });
+            for name in info.ancestors.iter().chain(std::iter::once(&cat.name)) {
+                categories
+                    .entry(name.clone())
+                    .or_default()
+                    .push(post_ref.clone());
+            }
         }
 
         let year = post.created_at.year();
@@ -38,7 +64,64 @@ pub fn build_taxonomy(posts: &[Post], _config: &SiteConfig) -> TaxonomyIndex {
         tags,
         categories,
         archives,
+        tag_info,
+        category_info,
+    }
+}
+
+/// 由分类表构建以名称为键的层级信息：父分类、子分类和祖先链
+fn build_category_info(db_categories: &[DbCategory]) -> HashMap<String, CategoryInfo> {
+    let names_by_id: HashMap<&str, &str> = db_categories
+        .iter()
+        .map(|c| (c.id.as_str(), c.name.as_str()))
+        .collect();
+    let parent_of = |c: &DbCategory| -> Option<String> {
+        c.parent_id
+            .as_deref()
+            .and_then(|id| names_by_id.get(id))
+            .map(|name| name.to_string())
+    };
+    let parents: HashMap<&str, String> = db_categories
+        .iter()
+        .filter_map(|c| parent_of(c).map(|p| (c.name.as_str(), p)))
+        .collect();
+
+    let mut info: HashMap<String, CategoryInfo> = HashMap::new();
+    for c in db_categories {
+        // 沿 parent 链向上收集祖先，遇到环时截断
+        let mut ancestors = Vec::new();
+        let mut current = c.name.as_str();
+        while let Some(parent) = parents.get(current) {
+            if parent == &c.name || ancestors.contains(parent) {
+                tracing::warn!("分类 {} 的父级关系存在循环，已截断", c.name);
+                break;
+            }
+            ancestors.push(parent.clone());
+            current = parent;
+        }
+        ancestors.reverse();
+
+        let mut children: Vec<String> = parents
+            .iter()
+            .filter(|(_, p)| **p == c.name)
+            .map(|(child, _)| child.to_string())
+            .collect();
+        children.sort();
+
+        info.insert(
+            c.name.clone(),
+            CategoryInfo {
+                id: c.id.clone(),
+                name: c.name.clone(),
+                slug: c.slug.clone(),
+                description: c.description.clone(),
+                parent: parents.get(c.name.as_str()).cloned(),
+                children,
+                ancestors,
+            },
+        );
     }
+    info
 }
 
 fn post_to_ref(post: &Post) -> PostRef {
@@ -46,12 +129,14 @@ fn post_to_ref(post: &Post) -> PostRef {
         id: post.id.to_string(),
         slug: post.slug.clone(),
         title: post.title.clone(),
-        url: format!("/posts/{}/", post.slug),
+        url: post.url.clone(),
         excerpt: post.excerpt.clone(),
         cover_image: post.cover_image.clone(),
         created_at: post.created_at.to_rfc3339(),
-        tags: post.tags.clone(),
-        category: post.category.clone(),
+        tags: post.tag_names().into_iter().map(str::to_owned).collect(),
+        category: post.category_name().map(str::to_owned),
+        tag_terms: post.tags.clone(),
+        category_term: post.category.clone(),
         reading_time: post.reading_time,
     }
 }
@@ -3,12 +3,38 @@ use anyhow::Result;
 
 /// 将 AST 转换为 MiniJinja 模板字符串
 pub fn generate(ast: &Node) -> Result<String> {
-    let mut output = String::new();
+    let mut output = Output::default();
     generate_node(ast, &mut output, 0)?;
-    Ok(output)
+    Ok(output.buf)
 }
 
-fn generate_node(node: &Node, output: &mut String, _depth: usize) -> Result<()> {
+/// 生成中的模板字符串，追加时同步记录当前行号，供 `Node::Line` 对齐源码行
+struct Output {
+    buf: String,
+    line: usize,
+}
+
+impl Default for Output {
+    fn default() -> Self {
+        Self { buf: String::new(), line: 1 }
+    }
+}
+
+impl Output {
+    fn push_str(&mut self, s: &str) {
+        self.line += s.bytes().filter(|&b| b == b'\n').count();
+        self.buf.push_str(s);
+    }
+
+    fn push(&mut self, c: char) {
+        if c == '\n' {
+            self.line += 1;
+        }
+        self.buf.push(c);
+    }
+}
+
+fn generate_node(node: &Node, output: &mut Output, _depth: usize) -> Result<()> {
     match node {
         Node::Document { extends, children } => {
             if let Some(parent) = extends {
@@ -122,6 +148,15 @@ fn generate_node(node: &Node, output: &mut String, _depth: usize) -> Result<()>
         Node::Comment(_) => {
             // 注释不输出
         }
+        Node::Line(line) => {
+            // 用跨行的空注释补齐换行，使渲染错误报告的行号与 cbtml 源码一致，不影响输出内容
+            let current = output.line;
+            if *line > current {
+                output.push_str("{#");
+                output.push_str(&"\n".repeat(line - current));
+                output.push_str("#}");
+            }
+        }
         Node::Hook { name, data } => {
             // hook 调用映射为 MiniJinja 函数调用
             if data.is_empty() {
@@ -1,9 +1,10 @@
+use crate::build::routes::UrlResolver;
 use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use md5::{Digest as Md5Digest, Md5};
 use minijinja::{Environment, Value};
 
 /// 向 MiniJinja 环境注册所有内置过滤器
-pub fn register_filters(env: &mut Environment, site_url: &str) {
+pub fn register_filters(env: &mut Environment, site_url: &str, resolver: UrlResolver) {
     env.add_filter("date", filter_date);
     env.add_filter("iso", filter_iso);
     env.add_filter("slugify", filter_slugify);
@@ -11,8 +12,6 @@ pub fn register_filters(env: &mut Environment, site_url: &str) {
     env.add_filter("wordcount", filter_wordcount);
     env.add_filter("reading_time", filter_reading_time);
     env.add_filter("reading_time_label", filter_reading_time_label);
-    env.add_filter("tag_url", filter_tag_url);
-    env.add_filter("category_url", filter_category_url);
     env.add_filter("json", filter_json);
     env.add_filter("active_class", filter_active_class);
     env.add_filter("md5", filter_md5);
@@ -20,6 +19,19 @@ pub fn register_filters(env: &mut Environment, site_url: &str) {
     env.add_filter("lower", filter_lower);
     env.add_filter("capitalize", filter_capitalize);
 
+    // tag_url / category_url 按 [routes] 配置解析：接受标签/分类对象（取其 slug），
+    // 或名称字符串（优先使用表中存储的 slug）
+    let tag_resolver = resolver.clone();
+    env.add_filter("tag_url", move |tag: Value| -> String {
+        let slug = term_slug(&tag).unwrap_or_else(|| tag_resolver.tag_slug(&tag.to_string()));
+        tag_resolver.tag(&slug)
+    });
+    env.add_filter("category_url", move |category: Value| -> String {
+        let slug =
+            term_slug(&category).unwrap_or_else(|| resolver.category_slug(&category.to_string()));
+        resolver.category(&slug)
+    });
+
     let url = site_url.trim_end_matches('/').to_owned();
     env.add_filter("abs_url", move |path: String| -> String {
         let path = path.trim_start_matches('/');
@@ -27,6 +39,15 @@ pub fn register_filters(env: &mut Environment, site_url: &str) {
     });
 }
 
+/// 标签/分类对象的 slug 字段，字符串等其他值返回 None
+fn term_slug(value: &Value) -> Option<String> {
+    value
+        .get_attr("slug")
+        .ok()
+        .filter(|v| !v.is_undefined() && !v.is_none())
+        .map(|v| v.to_string())
+}
+
 /// 尝试从多种常见格式中解析日期字符串
 fn parse_datetime(s: &str) -> Option<DateTime<Utc>> {
     // RFC 3339 / ISO 8601 带时区
@@ -121,14 +142,6 @@ fn filter_reading_time_label(minutes: u32) -> String {
     }
 }
 
-fn filter_tag_url(tag: String) -> String {
-    format!("/tags/{}/", filter_slugify(tag))
-}
-
-fn filter_category_url(category: String) -> String {
-    format!("/category/{}/", filter_slugify(category))
-}
-
 fn filter_json(value: Value) -> Result<String, minijinja::Error> {
     let serialized = serde_json::to_string(&value).map_err(|e| {
         minijinja::Error::new(
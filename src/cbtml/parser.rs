@@ -54,6 +54,8 @@ pub enum Node {
     Comment(String),
     /// hook 调用
     Hook { name: String, data: String },
+    /// 源码行号标记：后续节点起始于 cbtml 第 N 行，供 codegen 对齐编译产物的行号
+    Line(usize),
 }
 
 /// 自闭合 void 元素
@@ -105,6 +107,13 @@ fn parse_children(
             break;
         }
 
+        if !matches!(
+            token.kind,
+            TokenKind::End | TokenKind::Else | TokenKind::ElseIf(_)
+        ) {
+            children.push(Node::Line(token.line));
+        }
+
         match &token.kind {
             // end / else / else if 是控制结构的边界，由上层处理
             TokenKind::End | TokenKind::Else | TokenKind::ElseIf(_) => break,
@@ -17,14 +17,39 @@ pub fn run(project_root: &Path) -> Result<CheckResult> {
     Ok(CheckResult { errors, warnings })
 }
 
-fn check_config(root: &Path, errors: &mut Vec<String>, _warnings: &mut Vec<String>) {
+fn check_config(root: &Path, errors: &mut Vec<String>, warnings: &mut Vec<String>) {
     let config_path = root.join("cblog.toml");
     if !config_path.exists() {
         errors.push("缺少 cblog.toml 配置文件".to_string());
         return;
     }
-    if let Err(e) = crate::config::SiteConfig::load(root) {
-        errors.push(format!("cblog.toml 解析失败: {e}"));
+    let config = match crate::config::SiteConfig::load(root) {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            errors.push(format!("cblog.toml 解析失败: {e}"));
+            return;
+        }
+    };
+
+    // 文章 URL 必须包含唯一标识，否则不同文章会写入同一路径
+    let post_url = &config.routes.post_url;
+    if !post_url.contains("{slug}") && !post_url.contains("{id}") {
+        warnings.push(format!(
+            "routes.post_url \"{post_url}\" 不包含 {{slug}} 或 {{id}}，文章 URL 可能冲突"
+        ));
+    }
+    for (key, pattern) in [
+        ("tag_url", &config.routes.tag_url),
+        ("category_url", &config.routes.category_url),
+        ("page_url", &config.routes.page_url),
+    ] {
+        if !pattern.contains("{slug}") {
+            warnings.push(format!("routes.{key} \"{pattern}\" 不包含 {{slug}}"));
+        }
+    }
+    let year_url = &config.routes.archive_year_url;
+    if !year_url.contains("{year}") {
+        warnings.push(format!("routes.archive_year_url \"{year_url}\" 不包含 {{year}}"));
     }
 }
 
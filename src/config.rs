@@ -72,6 +72,71 @@ pub struct BuildConfig {
     pub excerpt_length: usize,
     #[serde(default = "default_true")]
     pub parallel: bool,
+    #[serde(default)]
+    pub pagination: PaginationConfig,
+    /// 保留的历史输出版本数（用于回滚），0 表示不保留
+    #[serde(default = "default_keep_releases")]
+    pub keep_releases: usize,
+    /// 严格模式：任一页面渲染失败即判定构建失败，不切换输出
+    #[serde(default)]
+    pub strict: bool,
+    #[serde(default)]
+    pub minify: MinifyConfig,
+    /// 主题 CSS/JS 额外输出带内容哈希的文件名（保留原文件），模板中通过 asset_url() 引用
+    #[serde(default)]
+    pub fingerprint_assets: bool,
+    /// 媒体目录发布到输出目录的方式
+    #[serde(default)]
+    pub media_mode: MediaMode,
+    /// 在页面中注入实时刷新脚本，仅由 `cblog dev` 在运行时开启
+    #[serde(skip)]
+    pub live_reload: bool,
+    /// 同时构建草稿，仅由 `cblog build --drafts` 开启
+    #[serde(skip)]
+    pub drafts: bool,
+    /// 同时构建定时发布时间未到的文章，仅由 `cblog build --future` 开启
+    #[serde(skip)]
+    pub future: bool,
+    /// 命令行覆盖的站点地址（同时覆盖 site.url 与后台设置中的站点地址）
+    #[serde(skip)]
+    pub base_url: Option<String>,
+    /// 命令行指定的配置文件（代替项目根目录下的 cblog.toml）
+    #[serde(skip)]
+    pub config_file: Option<std::path::PathBuf>,
+}
+
+/// [build] media_mode：媒体文件如何进入输出目录的 media/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaMode {
+    /// 符号链接到项目的媒体目录
+    #[default]
+    Symlink,
+    /// 复制全部媒体文件
+    Copy,
+    /// 硬链接全部媒体文件，跨文件系统时退回复制
+    Hardlink,
+    /// 只复制渲染结果中引用到的媒体文件
+    Referenced,
+}
+
+/// [build.minify] 输出阶段压缩渲染后的 HTML 以及编译、复制的 CSS 和 JS
+#[derive(Debug, Default, Deserialize)]
+pub struct MinifyConfig {
+    #[serde(default)]
+    pub html: bool,
+    #[serde(default)]
+    pub css: bool,
+    #[serde(default)]
+    pub js: bool,
+}
+
+/// [build.pagination] 按归档类型覆盖每页文章数，未设置时使用 posts_per_page
+#[derive(Debug, Default, Deserialize)]
+pub struct PaginationConfig {
+    pub tag: Option<usize>,
+    pub category: Option<usize>,
+    pub archive: Option<usize>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -80,7 +145,6 @@ pub struct ThemeRef {
 }
 
 #[derive(Debug, Deserialize)]
-#[allow(dead_code)]
 pub struct RouteConfig {
     #[serde(default = "default_post_url")]
     pub post_url: String,
@@ -90,6 +154,16 @@ pub struct RouteConfig {
     pub category_url: String,
     #[serde(default = "default_archive_url")]
     pub archive_url: String,
+    #[serde(default = "default_page_url")]
+    pub page_url: String,
+    #[serde(default = "default_tag_index_url")]
+    pub tag_index_url: String,
+    #[serde(default = "default_category_index_url")]
+    pub category_index_url: String,
+    #[serde(default = "default_archive_index_url")]
+    pub archive_index_url: String,
+    #[serde(default = "default_archive_year_url")]
+    pub archive_year_url: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -112,6 +186,12 @@ pub struct FeedConfig {
     pub post_count: usize,
     #[serde(default)]
     pub full_content: bool,
+    /// 为每个标签生成订阅，写入标签页所在目录（如 /tags/{slug}/feed.xml）
+    #[serde(default)]
+    pub per_tag: bool,
+    /// 为每个分类生成订阅（含子分类文章）
+    #[serde(default)]
+    pub per_category: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -122,15 +202,45 @@ pub struct SitemapConfig {
     pub change_freq: String,
     #[serde(default = "default_priority")]
     pub priority: f32,
+    #[serde(default)]
+    pub types: SitemapTypesConfig,
+}
+
+/// [sitemap.types] 按页面类型覆盖 change_freq 与 priority
+#[derive(Debug, Default, Deserialize)]
+pub struct SitemapTypesConfig {
+    pub index: Option<SitemapEntryConfig>,
+    pub post: Option<SitemapEntryConfig>,
+    pub page: Option<SitemapEntryConfig>,
+    /// 标签页与标签索引页
+    pub tag: Option<SitemapEntryConfig>,
+    /// 分类页与分类索引页
+    pub category: Option<SitemapEntryConfig>,
+    /// 月份、年份归档与归档索引页
+    pub archive: Option<SitemapEntryConfig>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SitemapEntryConfig {
+    pub change_freq: Option<String>,
+    pub priority: Option<f32>,
 }
 
 impl SiteConfig {
     pub fn load(project_root: &Path) -> Result<Self> {
-        let config_path = project_root.join("cblog.toml");
-        let content = std::fs::read_to_string(&config_path)
-            .map_err(|e| anyhow::anyhow!("读取 cblog.toml 失败：{}", e))?;
+        Self::load_file(&project_root.join("cblog.toml"))
+    }
+
+    /// 从指定配置文件加载（`cblog build --config`）
+    pub fn load_file(config_path: &Path) -> Result<Self> {
+        let name = config_path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| config_path.display().to_string());
+        let content = std::fs::read_to_string(config_path)
+            .map_err(|e| anyhow::anyhow!("读取 {} 失败：{}", name, e))?;
         let config: SiteConfig = toml::from_str(&content)
-            .map_err(|e| anyhow::anyhow!("解析 cblog.toml 失败：{}", e))?;
+            .map_err(|e| anyhow::anyhow!("解析 {} 失败：{}", name, e))?;
         Ok(config)
     }
 }
@@ -143,11 +253,17 @@ fn default_cache_dir() -> String { ".cblog-cache".into() }
 fn default_posts_per_page() -> usize { 10 }
 fn default_date_format() -> String { "Y年m月d日".into() }
 fn default_excerpt_length() -> usize { 160 }
+fn default_keep_releases() -> usize { 3 }
 fn default_true() -> bool { true }
 fn default_post_url() -> String { "/posts/{slug}/".into() }
 fn default_tag_url() -> String { "/tags/{slug}/".into() }
 fn default_category_url() -> String { "/category/{slug}/".into() }
 fn default_archive_url() -> String { "/archive/{year}/{month}/".into() }
+fn default_page_url() -> String { "/{slug}/".into() }
+fn default_tag_index_url() -> String { "/tags/".into() }
+fn default_category_index_url() -> String { "/category/".into() }
+fn default_archive_index_url() -> String { "/archive/".into() }
+fn default_archive_year_url() -> String { "/archive/{year}/".into() }
 fn default_host() -> String { "127.0.0.1".into() }
 fn default_port() -> u16 { 3000 }
 fn default_log_level() -> String { "info".into() }
@@ -158,6 +274,7 @@ fn default_priority() -> f32 { 0.8 }
 fn default_jwt_secret() -> String { "CHANGE_ME_IN_PRODUCTION".into() }
 fn default_jwt_expires_in() -> String { "7d".into() }
 fn default_session_name() -> String { "cblog_session".into() }
+fn default_preview_expires_in() -> String { "3d".into() }
 fn default_upload_dir() -> String { "media".into() }
 fn default_max_file_size() -> String { "10MB".into() }
 fn default_allowed_types() -> Vec<String> {
@@ -170,6 +287,9 @@ fn default_allowed_types() -> Vec<String> {
 }
 fn default_webp_quality() -> u8 { 85 }
 fn default_thumb_width() -> u32 { 400 }
+fn default_image_formats() -> Vec<ImageVariantFormat> { vec![ImageVariantFormat::Original] }
+fn default_image_sizes() -> String { "100vw".into() }
+fn default_image_quality() -> u8 { 80 }
 
 impl Default for RouteConfig {
     fn default() -> Self {
@@ -178,6 +298,11 @@ impl Default for RouteConfig {
             tag_url: default_tag_url(),
             category_url: default_category_url(),
             archive_url: default_archive_url(),
+            page_url: default_page_url(),
+            tag_index_url: default_tag_index_url(),
+            category_index_url: default_category_index_url(),
+            archive_index_url: default_archive_index_url(),
+            archive_year_url: default_archive_year_url(),
         }
     }
 }
@@ -199,6 +324,8 @@ impl Default for FeedConfig {
             format: default_feed_formats(),
             post_count: default_feed_count(),
             full_content: false,
+            per_tag: false,
+            per_category: false,
         }
     }
 }
@@ -209,6 +336,7 @@ impl Default for SitemapConfig {
             enabled: true,
             change_freq: default_change_freq(),
             priority: default_priority(),
+            types: SitemapTypesConfig::default(),
         }
     }
 }
@@ -221,6 +349,9 @@ pub struct AuthConfig {
     pub jwt_expires_in: String,
     #[serde(default = "default_session_name")]
     pub session_name: String,
+    /// 预览分享链接的有效期
+    #[serde(default = "default_preview_expires_in")]
+    pub preview_expires_in: String,
 }
 
 impl Default for AuthConfig {
@@ -229,6 +360,7 @@ impl Default for AuthConfig {
             jwt_secret: default_jwt_secret(),
             jwt_expires_in: default_jwt_expires_in(),
             session_name: default_session_name(),
+            preview_expires_in: default_preview_expires_in(),
         }
     }
 }
@@ -289,14 +421,44 @@ pub struct FeaturesConfig {
 pub struct ImageOptimizeConfig {
     #[serde(default = "default_true")]
     pub enabled: bool,
+    /// 响应式图片的派生宽度，为空时不生成派生图
+    #[serde(default)]
+    pub widths: Vec<u32>,
+    /// 派生图格式，按浏览器优先选择的顺序排列，最后一项作为 <img> 的 srcset
+    #[serde(default = "default_image_formats")]
+    pub formats: Vec<ImageVariantFormat>,
+    /// 写入 <img> 与 <source> 的 sizes 属性
+    #[serde(default = "default_image_sizes")]
+    pub sizes: String,
+    /// JPEG 派生图的编码质量
+    #[serde(default = "default_image_quality")]
+    pub quality: u8,
 }
 
 impl Default for ImageOptimizeConfig {
     fn default() -> Self {
-        Self { enabled: true }
+        Self {
+            enabled: true,
+            widths: Vec::new(),
+            formats: default_image_formats(),
+            sizes: default_image_sizes(),
+            quality: default_image_quality(),
+        }
     }
 }
 
+/// 响应式派生图的编码格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageVariantFormat {
+    /// 与原图相同的格式
+    Original,
+    /// WebP（无损编码）
+    Webp,
+    Jpeg,
+    Png,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SyntaxHighlightConfig {
     #[serde(default = "default_true")]
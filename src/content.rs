@@ -43,15 +43,19 @@ impl MarkdownContent {
 pub struct Post {
     pub id: Ulid,
     pub slug: String,
+    /// 按 [routes] post_url 解析出的永久链接
+    pub url: String,
     pub title: String,
     pub content: MarkdownContent,
     pub status: PostStatus,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
-    pub tags: Vec<String>,
-    pub category: Option<String>,
+    pub tags: Vec<Term>,
+    pub category: Option<Term>,
     pub cover_image: Option<String>,
     pub excerpt: Option<String>,
+    /// 不希望被搜索引擎收录：页面输出 robots noindex，且不列入 sitemap
+    pub noindex: bool,
     pub author: Option<String>,
     pub template: Option<String>,
     pub layout: Option<String>,
@@ -61,13 +65,28 @@ pub struct Post {
     pub meta: HashMap<String, serde_json::Value>,
 }
 
+impl Post {
+    /// 标签名列表，模板与插件中的 `tags` 字段沿用名称形式
+    pub fn tag_names(&self) -> Vec<&str> {
+        self.tags.iter().map(|t| t.name.as_str()).collect()
+    }
+
+    /// 分类名，模板与插件中的 `category` 字段沿用名称形式
+    pub fn category_name(&self) -> Option<&str> {
+        self.category.as_ref().map(|c| c.name.as_str())
+    }
+}
+
 #[derive(Debug)]
 #[allow(dead_code)]
 pub struct Page {
     pub id: Ulid,
     pub slug: String,
+    /// 按 [routes] page_url 解析出的永久链接
+    pub url: String,
     pub title: String,
     pub content: MarkdownContent,
+    pub excerpt: String,
     pub status: PostStatus,
     pub template: Option<String>,
     pub created_at: DateTime<Utc>,
@@ -86,13 +105,117 @@ pub struct PostRef {
     pub created_at: String,
     pub tags: Vec<String>,
     pub category: Option<String>,
+    /// 标签对象（含 slug、描述与归档页 URL）
+    pub tag_terms: Vec<Term>,
+    pub category_term: Option<Term>,
     pub reading_time: u32,
 }
 
+/// 标签/分类对象，来自 tags / categories 表
+#[derive(Debug, Clone, Serialize)]
+pub struct Term {
+    /// 表 ID，仅存在于旧版 meta 字符串而未登记到表中的条目为空
+    pub id: String,
+    pub name: String,
+    pub slug: String,
+    pub description: String,
+    /// 按 [routes] 解析出的归档页 URL
+    pub url: String,
+}
+
 /// 分类索引
 #[derive(Debug, Default)]
 pub struct TaxonomyIndex {
     pub tags: HashMap<String, Vec<PostRef>>,
     pub categories: HashMap<String, Vec<PostRef>>,
     pub archives: std::collections::BTreeMap<(i32, u32), Vec<PostRef>>,
+    /// 标签对象，以标签名为键（与 tags 的键一致）
+    pub tag_info: HashMap<String, Term>,
+    /// 分类层级信息，以分类名为键（与 categories 的键一致）
+    pub category_info: HashMap<String, CategoryInfo>,
+}
+
+/// 分类表中的分类及其层级关系
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryInfo {
+    /// 分类表 ID，仅存在于文章 meta 而未登记的分类为空
+    pub id: String,
+    pub name: String,
+    pub slug: String,
+    pub description: String,
+    pub parent: Option<String>,
+    /// 直接子分类名，按名称排序
+    pub children: Vec<String>,
+    /// 从根分类到父分类的祖先链（不含自身）
+    pub ancestors: Vec<String>,
+}
+
+/// 单个年份的归档：(年份, [(月份, 文章)])
+pub type YearArchive<'a> = (i32, Vec<(u32, &'a [PostRef])>);
+
+/// 标签/分类的统计概要，weight 为标签云权重（1–5，按文章数线性分布）
+#[derive(Debug, Clone, Serialize)]
+pub struct TermSummary {
+    pub name: String,
+    pub count: usize,
+    pub weight: u8,
+}
+
+impl TaxonomyIndex {
+    /// 标签概要，按文章数降序、同数量按名称排序
+    pub fn tag_summaries(&self) -> Vec<TermSummary> {
+        let mut terms = summarize(&self.tags);
+        terms.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+        terms
+    }
+
+    /// 分类概要，按层级路径排序（父分类在前，子分类紧随其后）
+    pub fn category_summaries(&self) -> Vec<TermSummary> {
+        let mut terms = summarize(&self.categories);
+        terms.sort_by_cached_key(|t| self.category_path(&t.name));
+        terms
+    }
+
+    /// 从根分类到指定分类的名称路径
+    pub fn category_path(&self, name: &str) -> Vec<String> {
+        let mut path = self
+            .category_info
+            .get(name)
+            .map(|info| info.ancestors.clone())
+            .unwrap_or_default();
+        path.push(name.to_owned());
+        path
+    }
+
+    /// 按年份聚合的月份归档，年份与月份均为降序
+    pub fn years(&self) -> Vec<YearArchive<'_>> {
+        let mut years: Vec<YearArchive<'_>> = Vec::new();
+        for ((year, month), posts) in self.archives.iter().rev() {
+            match years.last_mut() {
+                Some((y, months)) if y == year => months.push((*month, posts)),
+                _ => years.push((*year, vec![(*month, posts.as_slice())])),
+            }
+        }
+        years
+    }
+}
+
+fn summarize(terms: &HashMap<String, Vec<PostRef>>) -> Vec<TermSummary> {
+    let min = terms.values().map(Vec::len).min().unwrap_or(0);
+    let max = terms.values().map(Vec::len).max().unwrap_or(0);
+    terms
+        .iter()
+        .map(|(name, posts)| {
+            let weight = if max > min {
+                1 + ((posts.len() - min) * 4 / (max - min)) as u8
+            } else {
+                3
+            };
+            TermSummary {
+                name: name.clone(),
+                count: posts.len(),
+                weight,
+            }
+        })
+        .collect()
 }
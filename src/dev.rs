@@ -0,0 +1,230 @@
+use crate::build;
+use crate::build::stages::postprocess::LIVE_RELOAD_PATH;
+use crate::config::SiteConfig;
+use anyhow::{Context, Result};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// 合并连续文件事件的等待时间（编辑器保存一次通常会触发多个事件）
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// 推送给浏览器刷新脚本的消息
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum DevEvent {
+    Reload,
+    Error { message: String },
+}
+
+/// 构建结果通知：广播给已打开的页面，并记住最近一次错误供新打开的页面显示
+#[derive(Clone)]
+struct Notifier {
+    sender: broadcast::Sender<DevEvent>,
+    last_error: Arc<Mutex<Option<String>>>,
+}
+
+impl Notifier {
+    fn reload(&self) {
+        *self.last_error.lock().unwrap() = None;
+        let _ = self.sender.send(DevEvent::Reload);
+    }
+
+    fn error(&self, message: String) {
+        *self.last_error.lock().unwrap() = Some(message.clone());
+        let _ = self.sender.send(DevEvent::Error { message });
+    }
+}
+
+/// 启动开发服务器：首次构建后监听主题目录、插件与 cblog.toml，变更时重建并通知浏览器
+pub async fn run(root: PathBuf, mut config: SiteConfig, host: &str, port: u16) -> Result<()> {
+    config.build.live_reload = true;
+
+    let (sender, _) = broadcast::channel(16);
+    let notifier = Notifier {
+        sender,
+        last_error: Arc::new(Mutex::new(None)),
+    };
+
+    let output_dir = root.join(&config.build.output_dir);
+    let media_dir = root.join(&config.media.upload_dir);
+
+    // 文件监听与构建在独立线程中串行执行，构建期间到达的变更留到下一轮处理
+    let watch_root = root.clone();
+    let watch_notifier = notifier.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = watch_loop(&watch_root, config, &watch_notifier) {
+            tracing::error!("文件监听已停止：{e:#}");
+        }
+    });
+
+    let static_site = tower_http::services::ServeDir::new(&output_dir)
+        .append_index_html_on_directories(true)
+        .not_found_service(tower_http::services::ServeFile::new(output_dir.join("404.html")));
+    let app = Router::new()
+        .route(LIVE_RELOAD_PATH, get(live_reload_ws))
+        .nest_service("/media", tower_http::services::ServeDir::new(media_dir))
+        .fallback_service(static_site)
+        .with_state(notifier);
+
+    let addr = format!("{host}:{port}");
+    let listener = tokio::net::TcpListener::bind(&addr)
+        .await
+        .with_context(|| format!("监听 {addr} 失败"))?;
+    tracing::info!("开发服务器启动：http://{addr}");
+
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+fn watch_loop(root: &Path, mut config: SiteConfig, notifier: &Notifier) -> Result<()> {
+    let config_path = root.join("cblog.toml");
+    let plugins_dir = root.join("plugins");
+    // 监听整个 themes/ 目录：当前主题的父主题提供的模板与资源同样参与构建
+    let themes_dir = root.join("themes");
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(&themes_dir, RecursiveMode::Recursive)?;
+    if plugins_dir.exists() {
+        watcher.watch(&plugins_dir, RecursiveMode::Recursive)?;
+    }
+    // 监听项目根目录而非 cblog.toml 本身：编辑器以替换文件的方式保存时，文件级监听会失效
+    watcher.watch(root, RecursiveMode::NonRecursive)?;
+
+    rebuild(root, &config, false, notifier);
+    tracing::info!("正在监听变更：{}、{}、{}", themes_dir.display(), plugins_dir.display(), config_path.display());
+
+    while let Ok(first) = rx.recv() {
+        let mut events = vec![first];
+        while let Ok(event) = rx.recv_timeout(DEBOUNCE) {
+            events.push(event);
+        }
+
+        let changed: Vec<PathBuf> = events
+            .into_iter()
+            .filter_map(|event| event.ok())
+            .filter(|event| !matches!(event.kind, EventKind::Access(_)))
+            .flat_map(|event| event.paths)
+            .filter(|path| {
+                if path.parent() == Some(root) {
+                    return *path == config_path;
+                }
+                !is_editor_temp(path)
+            })
+            .collect();
+        if changed.is_empty() {
+            continue;
+        }
+        for path in &changed {
+            tracing::info!("检测到变更：{}", path.strip_prefix(root).unwrap_or(path).display());
+        }
+
+        if changed.contains(&config_path) {
+            match SiteConfig::load(root) {
+                Ok(mut reloaded) => {
+                    reloaded.build.live_reload = true;
+                    config = reloaded;
+                }
+                Err(e) => {
+                    tracing::error!("重新加载配置失败：{e:#}");
+                    notifier.error(format!("cblog.toml 解析失败：{e:#}"));
+                    continue;
+                }
+            }
+        }
+
+        // 插件脚本的变更不在哈希缓存的跟踪范围内，需要全量重建；模板变更由增量构建处理
+        let force = changed.iter().any(|path| path.starts_with(&plugins_dir));
+        rebuild(root, &config, force, notifier);
+    }
+    Ok(())
+}
+
+/// 编辑器产生的临时文件和隐藏文件（如 .swp、4913、foo~）不触发重建
+fn is_editor_temp(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return true;
+    };
+    name.starts_with('.')
+        || name.ends_with('~')
+        || name.ends_with(".swp")
+        || name.ends_with(".tmp")
+        || name.chars().all(|c| c.is_ascii_digit())
+}
+
+fn rebuild(root: &Path, config: &SiteConfig, force: bool, notifier: &Notifier) {
+    let params = build::BuildParams {
+        force,
+        ..build::load_params_sync(root, config)
+    };
+    match build::run(root, config, params) {
+        Ok(stats) if stats.render_errors.is_empty() => notifier.reload(),
+        Ok(stats) => {
+            let message = stats
+                .render_errors
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n");
+            tracing::warn!("{} 个页面渲染失败", stats.render_errors.len());
+            notifier.error(message);
+        }
+        Err(e) => {
+            tracing::error!("构建失败：{e:#}");
+            notifier.error(format!("{e:#}"));
+        }
+    }
+}
+
+async fn live_reload_ws(
+    ws: WebSocketUpgrade,
+    State(notifier): State<Notifier>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_ws(socket, notifier))
+}
+
+async fn handle_ws(mut socket: WebSocket, notifier: Notifier) {
+    let mut rx = notifier.sender.subscribe();
+
+    // 新打开的页面立即显示尚未修复的构建错误
+    let pending = notifier.last_error.lock().unwrap().clone();
+    if let Some(message) = pending
+        && send_event(&mut socket, &DevEvent::Error { message }).await.is_err()
+    {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            event = rx.recv() => match event {
+                Ok(event) => {
+                    if send_event(&mut socket, &event).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            // 客户端断开时结束，不必等到下一次构建
+            msg = socket.recv() => {
+                if !matches!(msg, Some(Ok(_))) {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+async fn send_event(socket: &mut WebSocket, event: &DevEvent) -> Result<(), axum::Error> {
+    let json = serde_json::to_string(event).unwrap_or_default();
+    socket.send(Message::Text(json.into())).await
+}
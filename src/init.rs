@@ -31,6 +31,10 @@ const TPL_404: &str = include_str!("../themes/aurora/templates/404.cbtml");
 const TPL_ARCHIVE: &str = include_str!("../themes/aurora/templates/archive.cbtml");
 const TPL_CATEGORY: &str = include_str!("../themes/aurora/templates/category.cbtml");
 const TPL_TAG: &str = include_str!("../themes/aurora/templates/tag.cbtml");
+const TPL_TAGS: &str = include_str!("../themes/aurora/templates/tags.cbtml");
+const TPL_CATEGORIES: &str = include_str!("../themes/aurora/templates/categories.cbtml");
+const TPL_ARCHIVE_YEAR: &str = include_str!("../themes/aurora/templates/archive-year.cbtml");
+const TPL_ARCHIVES: &str = include_str!("../themes/aurora/templates/archives.cbtml");
 
 const TPL_NAV: &str = include_str!("../themes/aurora/templates/partials/nav.cbtml");
 const TPL_FOOTER: &str = include_str!("../themes/aurora/templates/partials/footer.cbtml");
@@ -75,6 +79,10 @@ pub fn ensure_initialized(root: &Path) -> Result<bool> {
         ("themes/aurora/templates/archive.cbtml", TPL_ARCHIVE),
         ("themes/aurora/templates/category.cbtml", TPL_CATEGORY),
         ("themes/aurora/templates/tag.cbtml", TPL_TAG),
+        ("themes/aurora/templates/tags.cbtml", TPL_TAGS),
+        ("themes/aurora/templates/categories.cbtml", TPL_CATEGORIES),
+        ("themes/aurora/templates/archive-year.cbtml", TPL_ARCHIVE_YEAR),
+        ("themes/aurora/templates/archives.cbtml", TPL_ARCHIVES),
         ("themes/aurora/templates/partials/nav.cbtml", TPL_NAV),
         ("themes/aurora/templates/partials/footer.cbtml", TPL_FOOTER),
         (
@@ -2,11 +2,20 @@ use mlua::{LuaSerdeExt, RegistryKey};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// 已注册的 hook 处理器
+struct Handler {
+    priority: i32,
+    /// 注册该处理器的插件
+    plugin: String,
+    key: RegistryKey,
+}
 
 /// Hook 注册表：管理 filter 和 action 两类 hook
 pub struct HookRegistry {
-    filters: HashMap<String, Vec<(i32, RegistryKey)>>,
-    actions: HashMap<String, Vec<(i32, RegistryKey)>>,
+    filters: HashMap<String, Vec<Handler>>,
+    actions: HashMap<String, Vec<Handler>>,
 }
 
 impl HookRegistry {
@@ -17,18 +26,26 @@ impl HookRegistry {
         }
     }
 
-    pub fn add_filter(&mut self, hook: &str, priority: i32, key: RegistryKey) {
+    pub fn add_filter(&mut self, hook: &str, priority: i32, plugin: &str, key: RegistryKey) {
         self.filters
             .entry(hook.to_string())
             .or_default()
-            .push((priority, key));
+            .push(Handler {
+                priority,
+                plugin: plugin.to_string(),
+                key,
+            });
     }
 
-    pub fn add_action(&mut self, hook: &str, priority: i32, key: RegistryKey) {
+    pub fn add_action(&mut self, hook: &str, priority: i32, plugin: &str, key: RegistryKey) {
         self.actions
             .entry(hook.to_string())
             .or_default()
-            .push((priority, key));
+            .push(Handler {
+                priority,
+                plugin: plugin.to_string(),
+                key,
+            });
     }
 
     /// 执行 filter hook：数据依次流经所有按优先级排序的处理器
@@ -48,10 +65,10 @@ impl HookRegistry {
         };
 
         let mut sorted: Vec<_> = handlers.iter().collect();
-        sorted.sort_by_key(|(p, _)| *p);
+        sorted.sort_by_key(|h| h.priority);
 
         let mut current = value;
-        for (priority, key) in sorted {
+        for Handler { priority, key, .. } in sorted {
             let func: mlua::Function = lua
                 .registry_value(key)
                 .map_err(|e| anyhow::anyhow!("获取 filter '{}' handler 失败: {}", hook, e))?;
@@ -81,30 +98,52 @@ impl HookRegistry {
         hook: &str,
         ctx: &T,
     ) -> anyhow::Result<()>
+    where
+        T: Serialize,
+    {
+        self.call_action_timed(lua, hook, ctx).map(|_| ())
+    }
+
+    /// 执行 action hook 并返回每个处理器所属插件及其耗时
+    pub fn call_action_timed<T>(
+        &self,
+        lua: &mlua::Lua,
+        hook: &str,
+        ctx: &T,
+    ) -> anyhow::Result<Vec<(String, Duration)>>
     where
         T: Serialize,
     {
         let handlers = match self.actions.get(hook) {
             Some(h) => h,
-            None => return Ok(()),
+            None => return Ok(Vec::new()),
         };
 
         let mut sorted: Vec<_> = handlers.iter().collect();
-        sorted.sort_by_key(|(p, _)| *p);
+        sorted.sort_by_key(|h| h.priority);
 
         let lua_ctx = lua
             .to_value(ctx)
             .map_err(|e| anyhow::anyhow!("序列化 action '{}' 上下文失败: {}", hook, e))?;
-        for (priority, key) in sorted {
+        let mut timings = Vec::with_capacity(sorted.len());
+        for Handler { priority, plugin, key } in sorted {
             let func: mlua::Function = lua
                 .registry_value(key)
                 .map_err(|e| anyhow::anyhow!("获取 action '{}' handler 失败: {}", hook, e))?;
+            let started = Instant::now();
             func.call::<()>(lua_ctx.clone()).map_err(|e| {
-                anyhow::anyhow!("action '{}' 执行失败 (priority={}): {}", hook, priority, e)
+                anyhow::anyhow!(
+                    "action '{}' 执行失败 (plugin={}, priority={}): {}",
+                    hook,
+                    plugin,
+                    priority,
+                    e
+                )
             })?;
+            timings.push((plugin.clone(), started.elapsed()));
         }
 
-        Ok(())
+        Ok(timings)
     }
 
     #[allow(dead_code)]
@@ -284,6 +284,7 @@ impl PluginEngine {
                         std::fs::create_dir_all(parent)
                             .map_err(|e| mlua::Error::external(format!("创建目录失败: {e}")))?;
                     }
+                    detach_output(&full)?;
                     std::fs::write(&full, content)
                         .map_err(|e| mlua::Error::external(format!("写入文件失败: {e}")))
                 })
@@ -368,6 +369,7 @@ impl PluginEngine {
                         std::fs::create_dir_all(parent)
                             .map_err(|e| mlua::Error::external(format!("创建目录失败: {e}")))?;
                     }
+                    detach_output(&dst_full)?;
                     std::fs::copy(&src_full, &dst_full)
                         .map_err(|e| mlua::Error::external(format!("复制文件失败: {e}")))?;
                     Ok(())
@@ -383,6 +385,7 @@ impl PluginEngine {
                 lua.create_function(move |_, (path, content): (String, String)| {
                     let full = sandbox::resolve_path(&root_c, &path)?;
                     use std::io::Write;
+                    detach_output(&full)?;
                     let mut file = std::fs::OpenOptions::new()
                         .create(true)
                         .append(true)
@@ -600,7 +603,7 @@ impl PluginEngine {
             }
 
             // 收集 pending hooks 注册到 HookRegistry
-            self.collect_pending_hooks()?;
+            self.collect_pending_hooks(name)?;
 
             tracing::info!("已加载插件: {} v{}", info.name, info.version);
             self.plugins.push(info);
@@ -705,7 +708,7 @@ impl PluginEngine {
     }
 
     /// 从 Lua _pending_hooks 表收集 hook 注册到 Rust HookRegistry
-    fn collect_pending_hooks(&mut self) -> Result<()> {
+    fn collect_pending_hooks(&mut self, plugin_name: &str) -> Result<()> {
         let lua = &self.lua;
         let globals = lua.globals();
 
@@ -725,7 +728,7 @@ impl PluginEngine {
             let key = lua
                 .create_registry_value(func)
                 .map_err(|e| anyhow::anyhow!("{e}"))?;
-            self.hooks.add_filter(&hook, priority, key);
+            self.hooks.add_filter(&hook, priority, plugin_name, key);
         }
 
         // 收集 actions
@@ -740,7 +743,7 @@ impl PluginEngine {
             let key = lua
                 .create_registry_value(func)
                 .map_err(|e| anyhow::anyhow!("{e}"))?;
-            self.hooks.add_action(&hook, priority, key);
+            self.hooks.add_action(&hook, priority, plugin_name, key);
         }
 
         // 清空 pending
@@ -990,3 +993,9 @@ fn compute_s3_signature(
 
     Ok(result)
 }
+
+/// 构建期间暂存目录中的文件与线上输出共享硬链接，插件原地写入前先替换为独立副本
+fn detach_output(path: &std::path::Path) -> mlua::Result<()> {
+    crate::build::release::detach_file(path)
+        .map_err(|e| mlua::Error::external(format!("写入文件失败: {e}")))
+}
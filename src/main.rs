@@ -7,6 +7,7 @@ mod cbtml;
 mod check;
 mod config;
 mod content;
+mod dev;
 mod init;
 mod lua;
 mod media;
@@ -34,6 +35,30 @@ enum Commands {
         #[arg(long)]
         force: bool,
 
+        /// 构建结束后输出各阶段、插件钩子及最慢模板/页面的耗时表
+        #[arg(long)]
+        profile: bool,
+
+        /// 同时构建草稿（文章与页面）
+        #[arg(long)]
+        drafts: bool,
+
+        /// 同时构建定时发布时间未到的文章
+        #[arg(long)]
+        future: bool,
+
+        /// 覆盖输出目录（相对路径按当前目录解析），使用独立的构建缓存
+        #[arg(long, value_name = "DIR")]
+        output: Option<PathBuf>,
+
+        /// 覆盖站点地址（site.url 与后台设置中的站点地址）
+        #[arg(long, value_name = "URL")]
+        base_url: Option<String>,
+
+        /// 使用指定的配置文件代替项目根目录下的 cblog.toml
+        #[arg(long, value_name = "FILE")]
+        config: Option<PathBuf>,
+
         /// 项目根目录（默认当前目录）
         #[arg(short, long, default_value = ".")]
         root: PathBuf,
@@ -54,6 +79,21 @@ enum Commands {
         port: Option<u16>,
     },
 
+    /// 启动开发服务器：监听主题、插件与配置变更，自动重建并刷新浏览器
+    Dev {
+        /// 项目根目录（默认当前目录）
+        #[arg(short, long, default_value = ".")]
+        root: PathBuf,
+
+        /// 监听地址
+        #[arg(long)]
+        host: Option<String>,
+
+        /// 监听端口
+        #[arg(long)]
+        port: Option<u16>,
+    },
+
     /// 检查项目完整性
     Check {
         /// 项目根目录（默认当前目录）
@@ -72,10 +112,17 @@ fn main() -> anyhow::Result<()> {
         port: None,
     });
 
-    // 对于需要加载配置的命令，使用配置中的日志级别作为默认值
+    // 对于需要加载配置的命令，使用配置中的日志级别作为默认值；
+    // build 指定了 --config 时以该文件为准，与构建使用的配置一致
     let default_level = match &command {
+        Commands::Build {
+            config: Some(path), ..
+        } => config::SiteConfig::load_file(path)
+            .ok()
+            .map(|c| c.server.log_level.clone()),
         Commands::Build { root, .. }
         | Commands::Serve { root, .. }
+        | Commands::Dev { root, .. }
         | Commands::Check { root, .. } => {
             config::SiteConfig::load(&root.canonicalize().unwrap_or_else(|_| root.clone()))
                 .ok()
@@ -93,30 +140,49 @@ fn main() -> anyhow::Result<()> {
         .init();
 
     match command {
-        Commands::Build { clean, force, root } => {
+        Commands::Build {
+            clean,
+            force,
+            profile,
+            drafts,
+            future,
+            output,
+            base_url,
+            config,
+            root,
+        } => {
             let root = root.canonicalize()?;
             if init::ensure_initialized(&root)? {
                 tracing::info!("已自动初始化项目");
             }
-            let site_config = config::SiteConfig::load(&root)?;
-            let plugin_configs = plugin::store::load_all_configs_sync(
-                &root.join("cblog.db"),
-                &site_config.plugins.enabled,
-            );
-            let theme_saved_config = theme::config::load_theme_config_sync(
-                &root.join("cblog.db"),
-                &site_config.theme.active,
-            );
-            let db_posts = build::stages::load::fetch_db_posts_sync(&root.join("cblog.db"));
-            let site_settings = admin::settings::SiteSettings::load_sync(&root.join("cblog.db"));
-            let _stats = build::run(&root, &site_config, build::BuildParams {
+            let mut site_config = match &config {
+                Some(path) => config::SiteConfig::load_file(path)?,
+                None => config::SiteConfig::load(&root)?,
+            };
+            site_config.build.config_file = config;
+            site_config.build.drafts = drafts;
+            site_config.build.future = future;
+            if let Some(url) = base_url {
+                let url = url.trim_end_matches('/').to_owned();
+                site_config.site.url = url.clone();
+                site_config.build.base_url = Some(url);
+            }
+            if let Some(output) = output {
+                let output = std::env::current_dir()?.join(output);
+                // 不同输出目录各自维护哈希缓存、输出清单与历史版本，互不影响增量判断
+                let key = build::incremental::HashCache::hash_bytes(output.to_string_lossy().as_bytes());
+                site_config.build.cache_dir = format!("{}/outputs/{}", site_config.build.cache_dir, &key[..12]);
+                site_config.build.output_dir = output.to_string_lossy().into_owned();
+                tracing::info!("输出目录：{}", output.display());
+            }
+            let stats = build::run(&root, &site_config, build::BuildParams {
                 clean,
                 force,
-                plugin_configs,
-                theme_saved_config,
-                db_posts,
-                site_settings,
+                ..build::load_params_sync(&root, &site_config)
             })?;
+            if profile {
+                print!("{}", stats.profile.table());
+            }
         }
         Commands::Serve { root, host, port } => {
             let root = root.canonicalize()?;
@@ -135,6 +201,21 @@ fn main() -> anyhow::Result<()> {
                     run_server(root, site_config, &host, port).await
                 })?;
         }
+        Commands::Dev { root, host, port } => {
+            let root = root.canonicalize()?;
+            if init::ensure_initialized(&root)? {
+                tracing::info!("已自动初始化项目");
+            }
+            let site_config = config::SiteConfig::load(&root)?;
+
+            let host = host.unwrap_or_else(|| site_config.server.host.clone());
+            let port = port.unwrap_or(site_config.server.port);
+
+            tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()?
+                .block_on(dev::run(root, site_config, &host, port))?;
+        }
         Commands::Check { root } => {
             let root = root.canonicalize()?;
             let result = check::run(&root)?;
@@ -174,6 +255,8 @@ async fn run_server(
 
     // 启动后台定时清理过期 token
     admin::cleanup::spawn_token_cleanup(app_state.clone());
+    // 启动定时发布/到期下线检查
+    admin::scheduler::spawn_post_scheduler(app_state.clone());
 
     let app = admin::router(app_state);
 
@@ -50,6 +50,23 @@ pub fn process_image(input: &[u8], config: &MediaConfig) -> Result<ProcessedImag
     })
 }
 
+/// 按指定格式编码构建时生成的派生图：WebP 为无损编码，JPEG 按给定质量编码
+pub fn encode_variant(img: &image::DynamicImage, format: ImageFormat, quality: u8) -> Result<Vec<u8>> {
+    match format {
+        ImageFormat::WebP => encode_webp(img),
+        ImageFormat::Jpeg => {
+            let mut buf = Cursor::new(Vec::new());
+            let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut buf, quality);
+            // JPEG 不支持透明通道
+            image::DynamicImage::ImageRgb8(img.to_rgb8())
+                .write_with_encoder(encoder)
+                .context("JPEG 编码失败")?;
+            Ok(buf.into_inner())
+        }
+        _ => encode_as_format(img, Some(format)),
+    }
+}
+
 fn encode_webp(img: &image::DynamicImage) -> Result<Vec<u8>> {
     let mut buf = Cursor::new(Vec::new());
     let encoder = image::codecs::webp::WebPEncoder::new_lossless(&mut buf);
@@ -65,7 +82,7 @@ fn encode_as_format(img: &image::DynamicImage, format: Option<ImageFormat>) -> R
     Ok(buf.into_inner())
 }
 
-fn format_to_mime(format: Option<ImageFormat>) -> String {
+pub fn format_to_mime(format: Option<ImageFormat>) -> String {
     match format {
         Some(ImageFormat::Jpeg) => "image/jpeg",
         Some(ImageFormat::Png) => "image/png",
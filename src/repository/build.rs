@@ -19,6 +19,10 @@ pub struct BuildHistoryParams<'a> {
     pub total_pages: Option<i64>,
     pub rebuilt: Option<i64>,
     pub cached: Option<i64>,
+    /// 渲染失败页面的 JSON 数组
+    pub render_errors: Option<&'a str>,
+    /// 构建性能剖析 JSON
+    pub profile: Option<&'a str>,
 }
 
 impl BuildRepository {
@@ -28,7 +32,7 @@ impl BuildRepository {
 
     pub async fn list_history(&self, limit: i32) -> Vec<sqlx::sqlite::SqliteRow> {
         sqlx::query(
-            "SELECT trigger, status, duration_ms, error, started_at, finished_at \
+            "SELECT id, trigger, status, duration_ms, error, started_at, finished_at, render_errors \
              FROM build_history ORDER BY started_at DESC LIMIT ?",
         )
         .bind(limit)
@@ -37,10 +41,24 @@ impl BuildRepository {
         .unwrap_or_default()
     }
 
+    /// 按 ID 获取单条构建记录（含性能剖析）
+    pub async fn get_history(&self, id: &str) -> Option<sqlx::sqlite::SqliteRow> {
+        sqlx::query(
+            "SELECT id, trigger, status, duration_ms, error, started_at, finished_at, \
+             total_pages, rebuilt, cached, render_errors, profile \
+             FROM build_history WHERE id = ?",
+        )
+        .bind(id)
+        .fetch_optional(&self.db)
+        .await
+        .ok()
+        .flatten()
+    }
+
     pub async fn insert_history(&self, p: &BuildHistoryParams<'_>) -> Result<()> {
         sqlx::query(
-            "INSERT INTO build_history (id, trigger, status, duration_ms, error, started_at, finished_at, total_pages, rebuilt, cached) \
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            "INSERT INTO build_history (id, trigger, status, duration_ms, error, started_at, finished_at, total_pages, rebuilt, cached, render_errors, profile) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(p.id)
         .bind(p.trigger)
@@ -52,6 +70,8 @@ impl BuildRepository {
         .bind(p.total_pages)
         .bind(p.rebuilt)
         .bind(p.cached)
+        .bind(p.render_errors)
+        .bind(p.profile)
         .execute(&self.db)
         .await?;
 
@@ -81,6 +81,16 @@ impl MediaRepository {
         Ok(())
     }
 
+    /// 全部媒体的 URL 与原图尺寸，构建时为响应式图片写入 width/height
+    pub async fn list_dimensions(&self) -> Vec<(String, Option<i64>, Option<i64>)> {
+        sqlx::query_as::<_, (String, Option<i64>, Option<i64>)>(
+            "SELECT url, width, height FROM media",
+        )
+        .fetch_all(&self.db)
+        .await
+        .unwrap_or_default()
+    }
+
     pub async fn get_urls(&self, id: &str) -> Option<(String, Option<String>)> {
         sqlx::query_as::<_, (String, Option<String>)>(
             "SELECT url, thumb_url FROM media WHERE id = ?",
@@ -122,6 +122,22 @@ impl PageRepository {
         Ok(())
     }
 
+    /// 构建时获取已发布页面
+    pub async fn fetch_published(&self) -> Vec<sqlx::sqlite::SqliteRow> {
+        self.fetch_for_build(false).await
+    }
+
+    /// 按构建选项获取页面，`drafts` 为 true 时同时包含草稿
+    pub async fn fetch_for_build(&self, drafts: bool) -> Vec<sqlx::sqlite::SqliteRow> {
+        let statuses = if drafts { "'published', 'draft'" } else { "'published'" };
+        sqlx::query(&format!(
+            "SELECT id, slug, title, content, template, created_at, updated_at FROM pages WHERE status IN ({statuses})"
+        ))
+        .fetch_all(&self.db)
+        .await
+        .unwrap_or_default()
+    }
+
     /// 仪表盘统计
     pub async fn count_active(&self) -> i64 {
         sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM pages WHERE status != 'archived'")
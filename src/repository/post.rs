@@ -11,6 +11,10 @@ pub struct PostWriteParams<'a> {
     pub meta: &'a str,
     pub tags_str: &'a str,
     pub category_str: &'a str,
+    /// 定时发布时间（RFC 3339，UTC）
+    pub publish_at: Option<&'a str>,
+    /// 到期下线时间（RFC 3339，UTC）
+    pub unpublish_at: Option<&'a str>,
 }
 
 /// 自动保存参数（不含 status）
@@ -103,7 +107,8 @@ impl PostRepository {
 
     pub async fn get_by_id(&self, id: &str) -> Option<sqlx::sqlite::SqliteRow> {
         sqlx::query(
-            "SELECT id, slug, title, content, status, meta FROM posts WHERE id = ?",
+            "SELECT id, slug, title, content, status, meta, publish_at, unpublish_at \
+             FROM posts WHERE id = ?",
         )
         .bind(id)
         .fetch_optional(&self.db)
@@ -118,7 +123,8 @@ impl PostRepository {
         let mut tx = self.db.begin().await?;
 
         sqlx::query(
-            "INSERT INTO posts (id, slug, title, content, status, created_at, updated_at, meta) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            "INSERT INTO posts (id, slug, title, content, status, created_at, updated_at, meta, publish_at, unpublish_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(p.id)
         .bind(p.slug)
@@ -128,6 +134,8 @@ impl PostRepository {
         .bind(&now)
         .bind(&now)
         .bind(p.meta)
+        .bind(p.publish_at)
+        .bind(p.unpublish_at)
         .execute(&mut *tx)
         .await?;
 
@@ -143,7 +151,8 @@ impl PostRepository {
         let mut tx = self.db.begin().await?;
 
         sqlx::query(
-            "UPDATE posts SET title = ?, slug = ?, content = ?, status = ?, meta = ?, updated_at = ? WHERE id = ?",
+            "UPDATE posts SET title = ?, slug = ?, content = ?, status = ?, meta = ?, updated_at = ?, \
+             publish_at = ?, unpublish_at = ? WHERE id = ?",
         )
         .bind(p.title)
         .bind(p.slug)
@@ -151,6 +160,8 @@ impl PostRepository {
         .bind(p.status)
         .bind(p.meta)
         .bind(&now)
+        .bind(p.publish_at)
+        .bind(p.unpublish_at)
         .bind(p.id)
         .execute(&mut *tx)
         .await?;
@@ -196,16 +207,46 @@ impl PostRepository {
         Ok(())
     }
 
+    /// 发布文章；设置了未来的 publish_at 时进入定时发布状态
     pub async fn publish(&self, id: &str) -> Result<()> {
         let now = chrono::Utc::now().to_rfc3339();
-        sqlx::query("UPDATE posts SET status = 'published', updated_at = ? WHERE id = ?")
-            .bind(&now)
-            .bind(id)
-            .execute(&self.db)
-            .await?;
+        sqlx::query(
+            "UPDATE posts SET status = CASE WHEN publish_at > ? THEN 'scheduled' ELSE 'published' END, \
+             updated_at = ? WHERE id = ?",
+        )
+        .bind(&now)
+        .bind(&now)
+        .bind(id)
+        .execute(&self.db)
+        .await?;
         Ok(())
     }
 
+    /// 将已到 publish_at 的定时文章切换为已发布，返回切换的文章 ID
+    pub async fn publish_due(&self, now: &str) -> Result<Vec<String>> {
+        let ids = sqlx::query_scalar(
+            "UPDATE posts SET status = 'published', updated_at = publish_at \
+             WHERE status = 'scheduled' AND publish_at <= ? RETURNING id",
+        )
+        .bind(now)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(ids)
+    }
+
+    /// 将已到 unpublish_at 的文章恢复为草稿并清除下线时间，返回切换的文章 ID
+    pub async fn expire_due(&self, now: &str) -> Result<Vec<String>> {
+        let ids = sqlx::query_scalar(
+            "UPDATE posts SET status = 'draft', unpublish_at = NULL, updated_at = ? \
+             WHERE status IN ('published', 'scheduled') AND unpublish_at <= ? RETURNING id",
+        )
+        .bind(now)
+        .bind(now)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(ids)
+    }
+
     pub async fn unpublish(&self, id: &str) -> Result<()> {
         let now = chrono::Utc::now().to_rfc3339();
         sqlx::query("UPDATE posts SET status = 'draft', updated_at = ? WHERE id = ?")
@@ -252,17 +293,123 @@ impl PostRepository {
         .unwrap_or_default()
     }
 
-    /// 构建时获取已发布文章
-    pub async fn fetch_published(&self) -> Vec<sqlx::sqlite::SqliteRow> {
-        sqlx::query(
-            "SELECT id, slug, title, content, status, created_at, updated_at, meta FROM posts WHERE status = 'published'",
+    /// 从关联表读取文章的标签名（逗号分隔）和分类名
+    pub async fn taxonomy_names(&self, id: &str) -> (String, String) {
+        let tags: Vec<String> = sqlx::query_scalar(
+            "SELECT t.name FROM post_tags pt JOIN tags t ON t.id = pt.tag_id \
+             WHERE pt.post_id = ? ORDER BY t.name",
+        )
+        .bind(id)
+        .fetch_all(&self.db)
+        .await
+        .unwrap_or_default();
+
+        let category: Option<String> = sqlx::query_scalar(
+            "SELECT c.name FROM post_categories pc JOIN categories c ON c.id = pc.category_id \
+             WHERE pc.post_id = ? LIMIT 1",
         )
+        .bind(id)
+        .fetch_optional(&self.db)
+        .await
+        .ok()
+        .flatten();
+
+        (tags.join(", "), category.unwrap_or_default())
+    }
+
+    /// 构建时获取已发布文章，附带关联表中的标签 ID（逗号分隔）和分类 ID
+    ///
+    /// 按当前时间判断定时发布与到期下线：未到 publish_at 或已过 unpublish_at 的文章不参与构建，
+    /// 已到时间但后台任务尚未切换状态的定时文章视为已发布（命令行构建不依赖 serve 的后台任务）。
+    /// 设置了 publish_at 的文章以其作为发布日期
+    pub async fn fetch_published(&self) -> Vec<sqlx::sqlite::SqliteRow> {
+        self.fetch_for_build(false, false).await
+    }
+
+    /// 按构建选项获取文章：`drafts` 同时包含草稿，`future` 同时包含发布时间未到的文章
+    pub async fn fetch_for_build(&self, drafts: bool, future: bool) -> Vec<sqlx::sqlite::SqliteRow> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let statuses = if drafts {
+            "'published', 'scheduled', 'draft'"
+        } else {
+            "'published', 'scheduled'"
+        };
+        let publish_filter = if future {
+            ""
+        } else {
+            "AND (publish_at IS NULL OR publish_at <= ?1) "
+        };
+        sqlx::query(&format!(
+            "{BUILD_POST_SELECT} WHERE status IN ({statuses}) {publish_filter}\
+             AND (unpublish_at IS NULL OR unpublish_at > ?1)"
+        ))
+        .bind(&now)
         .fetch_all(&self.db)
         .await
         .unwrap_or_default()
     }
+
+    /// 预览时获取与文章相邻的已发布文章，返回（较新一篇，较旧一篇），用于生成上下篇
+    ///
+    /// 发布日期相同的文章按 ID 排序，与构建时的文章顺序一致
+    pub async fn fetch_preview_neighbours(
+        &self,
+        id: &str,
+        created_at: &str,
+    ) -> (Option<sqlx::sqlite::SqliteRow>, Option<sqlx::sqlite::SqliteRow>) {
+        let now = chrono::Utc::now().to_rfc3339();
+        let fetch = |condition: &'static str, order: &'static str| {
+            let now = &now;
+            async move {
+                sqlx::query(&format!(
+                    "{BUILD_POST_SELECT} WHERE status IN ('published', 'scheduled') \
+                     AND (publish_at IS NULL OR publish_at <= ?1) \
+                     AND (unpublish_at IS NULL OR unpublish_at > ?1) AND {condition} \
+                     ORDER BY {order} LIMIT 1"
+                ))
+                .bind(now)
+                .bind(id)
+                .bind(created_at)
+                .fetch_optional(&self.db)
+                .await
+                .ok()
+                .flatten()
+            }
+        };
+        let newer = fetch(
+            "(COALESCE(publish_at, created_at) > ?3 OR (COALESCE(publish_at, created_at) = ?3 AND id < ?2))",
+            "COALESCE(publish_at, created_at) ASC, id DESC",
+        )
+        .await;
+        let older = fetch(
+            "(COALESCE(publish_at, created_at) < ?3 OR (COALESCE(publish_at, created_at) = ?3 AND id > ?2))",
+            "COALESCE(publish_at, created_at) DESC, id ASC",
+        )
+        .await;
+        (newer, older)
+    }
+
+    /// 预览时获取单篇文章（不论状态），字段与 `fetch_published` 一致
+    pub async fn fetch_for_preview(&self, id: &str) -> Option<sqlx::sqlite::SqliteRow> {
+        sqlx::query(&format!("{BUILD_POST_SELECT} WHERE id = ? AND status != 'archived'"))
+            .bind(id)
+            .fetch_optional(&self.db)
+            .await
+            .ok()
+            .flatten()
+    }
 }
 
+/// 构建所需的文章字段：状态统一视为已发布，定时发布的文章以 publish_at 作为发布日期
+const BUILD_POST_SELECT: &str = "SELECT id, slug, title, content, 'published' AS status, \
+     COALESCE(publish_at, created_at) AS created_at, \
+     MAX(updated_at, COALESCE(publish_at, updated_at)) AS updated_at, meta, \
+     (SELECT group_concat(pt.tag_id) FROM post_tags pt JOIN tags t ON t.id = pt.tag_id \
+      WHERE pt.post_id = posts.id) AS tag_ids, \
+     (SELECT pc.category_id FROM post_categories pc JOIN categories c ON c.id = pc.category_id \
+      WHERE pc.post_id = posts.id LIMIT 1) AS category_id \
+     FROM posts";
+
 fn generate_slug(name: &str) -> String {
     name.to_lowercase()
         .chars()
@@ -75,7 +75,7 @@ impl AppState {
         // 解析 JWT secret：配置文件显式设置 > 数据库持久化 > 自动生成
         let jwt_secret = resolve_jwt_secret(&config.auth.jwt_secret, &pool).await?;
 
-        let (build_events, _) = broadcast::channel::<BuildEvent>(64);
+        let (build_events, _) = broadcast::channel::<BuildEvent>(256);
 
         // 扫描已启用插件的 admin 页面声明
         let plugin_admin_pages = collect_plugin_admin_pages(&project_root, &config);
@@ -101,6 +101,7 @@ impl AppState {
             &project_root,
             &config.theme.active,
             &config.site.url,
+            &config.routes,
         )?;
 
         let is_https = config.site.url.starts_with("https://");
@@ -288,8 +289,25 @@ async fn migrate_post_taxonomy(pool: &SqlitePool) {
         let meta_str: String = row.get("meta");
         let meta: serde_json::Value = serde_json::from_str(&meta_str).unwrap_or_default();
 
+        // 关联表是标签/分类的唯一来源，已有关联的文章不再从 meta 回填，
+        // 避免在后台改名后又按旧名称重新创建标签/分类
+        let has_tags = sqlx::query("SELECT 1 FROM post_tags WHERE post_id = ? LIMIT 1")
+            .bind(&post_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .ok()
+            .flatten()
+            .is_some();
+        let has_category = sqlx::query("SELECT 1 FROM post_categories WHERE post_id = ? LIMIT 1")
+            .bind(&post_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .ok()
+            .flatten()
+            .is_some();
+
         // 迁移标签
-        let tags_str = meta["tags"].as_str().unwrap_or("");
+        let tags_str = if has_tags { "" } else { meta["tags"].as_str().unwrap_or("") };
         let tags: Vec<&str> = tags_str.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()).collect();
         for tag_name in tags {
             let slug = generate_slug(tag_name);
@@ -306,7 +324,11 @@ async fn migrate_post_taxonomy(pool: &SqlitePool) {
         }
 
         // 迁移分类
-        let category_str = meta["category"].as_str().unwrap_or("").trim();
+        let category_str = if has_category {
+            ""
+        } else {
+            meta["category"].as_str().unwrap_or("").trim()
+        };
         if !category_str.is_empty() {
             let slug = generate_slug(category_str);
             let cat_id = ensure_category(&mut tx, category_str, &slug).await;
@@ -10,6 +10,8 @@ pub struct ThemeToml {
     pub theme: ThemeMeta,
     #[serde(default)]
     pub config: Vec<ConfigField>,
+    #[serde(default)]
+    pub special_pages: Vec<SpecialPage>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -48,12 +50,25 @@ pub struct ConfigField {
     pub description: Option<String>,
 }
 
+/// [[special_pages]] 特殊页面声明：如 404、搜索页，以站点上下文单独渲染到固定路径
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpecialPage {
+    /// 模板名（不含 .cbtml 后缀）
+    pub template: String,
+    /// 输出路径，如 `/404.html`、`/search/`
+    pub output: String,
+    #[serde(default)]
+    pub title: String,
+}
+
 #[derive(Debug)]
 #[allow(dead_code)]
 pub struct ResolvedTheme {
     pub name: String,
     pub meta: ThemeMeta,
     pub config_schema: Vec<ConfigField>,
+    /// 合并继承链后的特殊页面，子主题可按输出路径覆盖父主题声明
+    pub special_pages: Vec<SpecialPage>,
     /// 继承链：从当前主题到根主题
     pub parent_chain: Vec<String>,
 }
@@ -109,12 +124,23 @@ pub fn resolve_theme(project_root: &Path, theme_name: &str) -> Result<ResolvedTh
         }
     }
 
+    let mut special_pages: Vec<SpecialPage> = Vec::new();
+    for toml in all_tomls.iter().rev() {
+        for page in &toml.special_pages {
+            match special_pages.iter_mut().find(|p| p.output == page.output) {
+                Some(existing) => *existing = page.clone(),
+                None => special_pages.push(page.clone()),
+            }
+        }
+    }
+
     let leaf = all_tomls.into_iter().next().unwrap();
 
     Ok(ResolvedTheme {
         name: theme_name.to_string(),
         meta: leaf.theme,
         config_schema: merged_fields,
+        special_pages,
         parent_chain: chain,
     })
 }
@@ -483,6 +483,26 @@ if (coverInput && coverPreview && coverPreviewImg) {
     });
 }
 
+// ── 定时发布 ──
+// 时间输入框按浏览器本地时区显示和编辑，修改后转换为 UTC 写入隐藏字段提交
+
+function toLocalInputValue(iso) {
+    const d = new Date(iso);
+    if (isNaN(d.getTime())) return '';
+    const pad = n => String(n).padStart(2, '0');
+    return d.getFullYear() + '-' + pad(d.getMonth() + 1) + '-' + pad(d.getDate())
+        + 'T' + pad(d.getHours()) + ':' + pad(d.getMinutes());
+}
+
+document.querySelectorAll('input[data-utc-target]').forEach(input => {
+    const hidden = document.getElementById(input.dataset.utcTarget);
+    if (!hidden) return;
+    if (hidden.value) input.value = toLocalInputValue(hidden.value);
+    input.addEventListener('change', () => {
+        hidden.value = input.value ? new Date(input.value).toISOString() : '';
+    });
+});
+
 // ── 自动保存 ──
 
 const postForm = document.getElementById('post-form');
@@ -515,6 +535,7 @@ if (postForm && editor) {
                 category: document.getElementById('category-input')?.value || '',
                 cover_image: document.getElementById('cover-input')?.value || '',
                 excerpt: document.querySelector('textarea[name="excerpt"]')?.value || '',
+                noindex: document.getElementById('noindex-input')?.checked || false,
                 slug: document.querySelector('input[name="slug"]')?.value || '',
             };
 
@@ -543,4 +564,40 @@ if (postForm && editor) {
     document.querySelector('input[name="title"]')?.addEventListener('input', scheduleAutosave);
     editor.on('update', scheduleAutosave);
     setInterval(doAutosave, 30000);
+
+    // 预览前先保存当前编辑内容；先同步打开窗口，避免保存完成后被浏览器拦截弹窗
+    document.getElementById('preview-link')?.addEventListener('click', async e => {
+        e.preventDefault();
+        const href = e.currentTarget.href;
+        const win = window.open('about:blank', '_blank');
+        if (saveTimer) clearTimeout(saveTimer);
+        await doAutosave();
+        if (win) win.location.href = href;
+    });
 }
+
+// ── 分享预览 ──
+
+document.getElementById('share-preview-btn')?.addEventListener('click', async e => {
+    const urlInput = document.getElementById('share-preview-url');
+    const hint = document.getElementById('share-preview-hint');
+    try {
+        const resp = await fetch('/admin/posts/' + e.currentTarget.dataset.postId + '/share', { method: 'POST' });
+        const data = await resp.json();
+        if (!resp.ok) throw new Error(data.error || '生成预览链接失败');
+
+        urlInput.value = new URL(data.url, window.location.origin).href;
+        urlInput.hidden = false;
+        urlInput.select();
+        hint.textContent = '链接有效期至 ' + new Date(data.expires_at).toLocaleString();
+        hint.hidden = false;
+        try {
+            await navigator.clipboard.writeText(urlInput.value);
+            showToast('预览链接已复制', 'success');
+        } catch {
+            showToast('预览链接已生成，请手动复制', 'info');
+        }
+    } catch (err) {
+        showToast(err.message, 'error');
+    }
+});